@@ -0,0 +1,10 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct BuildHighlight {}
+
+#[derive(Component)]
+pub struct BuildHud {}
+
+#[derive(Component)]
+pub struct BrushText {}
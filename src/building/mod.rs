@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+mod components;
+pub mod resources;
+pub mod systems;
+mod utils;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildPalette>()
+            .init_resource::<BuildTarget>()
+            .add_systems(OnEnter(GameState::Game), spawn_build_hud)
+            .add_systems(OnEnter(GameState::Menu), despawn_build_hud)
+            .add_systems(OnEnter(CraftingMenuState::Open), hide_build_target)
+            .add_systems(
+                Update,
+                (cycle_brush, pick_build_target, edit_terrain, show_build_target)
+                    .chain()
+                    .run_if(in_state(GameState::Game).and_then(in_state(CraftingMenuState::Closed))),
+            );
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::inventory::items::*;
+use crate::world::registry::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
+
+// Something the player can put down at the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Brush {
+    Tile(TileType),
+    Structure(StructureType),
+}
+
+impl Brush {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Brush::Tile(tile_type) => tile_type.name(),
+            Brush::Structure(structure_type) => structure_type.name(),
+        }
+    }
+
+    pub fn footprint(&self) -> (u8, u8) {
+        match self {
+            Brush::Tile(_) => (1, 1),
+            Brush::Structure(structure_type) => structure_type.footprint(),
+        }
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        match self {
+            Brush::Tile(tile_type) => tile_type.is_walkable(),
+            Brush::Structure(structure_type) => structure_type.is_walkable(),
+        }
+    }
+
+    // What the player pays to put it down, the same items harvesting it gives back
+    pub fn cost(&self) -> Vec<(ItemType, u32)> {
+        match self {
+            Brush::Tile(_) => vec![],
+            Brush::Structure(structure_type) => structure_type
+                .definition()
+                .harvest
+                .map(|harvest| harvest.drops)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// Every defined tile then every defined structure, in the order of their files.
+// Cycling past the last brush leaves the hands empty, which builds nothing.
+#[derive(Resource, Default)]
+pub struct BuildPalette {
+    pub brushes: Vec<Brush>,
+    pub selected: usize,
+}
+
+impl BuildPalette {
+    pub fn from_definitions() -> Self {
+        let tiles = tile_types().into_iter().map(Brush::Tile);
+        let structures = structure_definitions()
+            .into_iter()
+            .map(|definition| Brush::Structure(definition.name));
+        BuildPalette {
+            brushes: tiles.chain(structures).collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn brush(&self) -> Option<Brush> {
+        self.brushes.get(self.selected).copied()
+    }
+
+    pub fn cycle(&mut self, step: i32) {
+        if !self.brushes.is_empty() {
+            let slots = self.brushes.len() as i32 + 1;
+            self.selected = (self.selected as i32 + step).rem_euclid(slots) as usize;
+        }
+    }
+}
+
+// The tile under the cursor, and whether the selected brush could go there
+#[derive(Resource, Default)]
+pub struct BuildTarget {
+    pub tile: Option<IVec2>,
+    pub can_build: bool,
+}
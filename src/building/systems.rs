@@ -0,0 +1,197 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::building::components::*;
+use crate::building::resources::*;
+use crate::building::utils::*;
+use crate::components::*;
+use crate::controls::resources::*;
+use crate::inventory::components::*;
+use crate::player::utils::*;
+use crate::resources::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+const CAN_BUILD_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const CANNOT_BUILD_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.3);
+
+pub fn spawn_build_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut palette: ResMut<BuildPalette>,
+    mut reader: EventReader<GameStart>,
+) {
+    if let Some(_game_start) = reader.read().last() {
+        // Picks up any definitions reloaded since the last game
+        *palette = BuildPalette::from_definitions();
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: CAN_BUILD_COLOR,
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, OVERLAY_Z),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BuildHighlight {},
+        ));
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Px(84.0),
+                        width: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+                BuildHud {},
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        brush_label(&palette),
+                        TextStyle {
+                            font: asset_server.load("fonts/Righteous-Regular.ttf"),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    BrushText {},
+                ));
+            });
+    }
+}
+
+pub fn despawn_build_hud(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    hud_query: Query<Entity, Or<(With<BuildHud>, With<BuildHighlight>)>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        for hud_entity in hud_query.iter() {
+            commands.entity(hud_entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn cycle_brush(
+    actions: Res<ButtonInput<Action>>,
+    mut palette: ResMut<BuildPalette>,
+    mut text_query: Query<&mut Text, With<BrushText>>,
+) {
+    let step = match (actions.just_pressed(Action::NextBlock), actions.just_pressed(Action::PreviousBlock)) {
+        (true, false) => 1,
+        (false, true) => -1,
+        _ => return,
+    };
+    palette.cycle(step);
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = brush_label(&palette);
+    }
+}
+
+pub fn pick_build_target(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    player_query: Query<(&Transform, &Player, &Inventory)>,
+    the_world: Res<TheWorld>,
+    palette: Res<BuildPalette>,
+    mut build_target: ResMut<BuildTarget>,
+) {
+    let cursor = window_query
+        .get_single()
+        .ok()
+        .zip(camera_query.get_single().ok())
+        .and_then(|(window, (camera, camera_transform))| cursor_to_world(window, camera, camera_transform));
+    let (Some(cursor), Ok((transform, player, inventory))) = (cursor, player_query.get_single()) else {
+        *build_target = BuildTarget::default();
+        return;
+    };
+    let tile_pos = world_to_tile(cursor);
+    let ground = player_ground(transform, player);
+    *build_target = BuildTarget {
+        tile: in_reach(ground, tile_pos).then_some(tile_pos),
+        can_build: palette.brush().is_some_and(|brush| {
+            can_afford(inventory, brush) && can_build(&the_world, brush, tile_pos, ground)
+        }),
+    };
+}
+
+// Left click puts the brush down, tiles keep painting while it is held. Right click clears.
+pub fn edit_terrain(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut the_world: ResMut<TheWorld>,
+    palette: Res<BuildPalette>,
+    build_target: Res<BuildTarget>,
+    mut player_query: Query<(&Transform, &Player, &mut Inventory)>,
+) {
+    let (Some(tile_pos), Ok((transform, player, mut inventory))) = (build_target.tile, player_query.get_single_mut())
+    else {
+        return;
+    };
+    if mouse_input.just_pressed(MouseButton::Right) {
+        clear(&mut the_world, tile_pos, player_ground(transform, player));
+        return;
+    }
+    let Some(brush) = palette.brush() else {
+        return;
+    };
+    let painting = matches!(brush, Brush::Tile(_)) && mouse_input.pressed(MouseButton::Left);
+    if build_target.can_build && (painting || mouse_input.just_pressed(MouseButton::Left)) {
+        build_with(&mut the_world, &mut inventory, brush, tile_pos);
+    }
+}
+
+pub fn show_build_target(
+    palette: Res<BuildPalette>,
+    build_target: Res<BuildTarget>,
+    mut highlight_query: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<BuildHighlight>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = highlight_query.get_single_mut() else {
+        return;
+    };
+    let (Some(tile_pos), Some(brush)) = (build_target.tile, palette.brush()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    let footprint = brush.footprint();
+    let size = Vec2::new(footprint.0.max(1) as f32, footprint.1.max(1) as f32) * TILE_SIZE;
+    let position = tile_position(tile_pos);
+    // Covers the whole footprint, which grows right and up from the anchor tile
+    transform.translation.x = position.x + (size.x - TILE_SIZE) / 2.0;
+    transform.translation.y = position.y + (size.y - TILE_SIZE) / 2.0;
+    sprite.custom_size = Some(size);
+    sprite.color = if build_target.can_build {
+        CAN_BUILD_COLOR
+    } else {
+        CANNOT_BUILD_COLOR
+    };
+    *visibility = Visibility::Inherited;
+}
+
+pub fn hide_build_target(mut highlight_query: Query<&mut Visibility, With<BuildHighlight>>) {
+    for mut visibility in highlight_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn brush_label(palette: &BuildPalette) -> String {
+    let Some(brush) = palette.brush() else {
+        return "Build: nothing".to_string();
+    };
+    let cost: Vec<String> = brush
+        .cost()
+        .iter()
+        .map(|(item, count)| format!("{} {}", count, item.definition().label()))
+        .collect();
+    if cost.is_empty() {
+        format!("Build: {}", brush.label())
+    } else {
+        format!("Build: {} ({})", brush.label(), cost.join(" + "))
+    }
+}
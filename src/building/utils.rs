@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::building::resources::*;
+use crate::inventory::components::*;
+use crate::player::utils::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+// How far from the player tiles can be changed
+const BUILD_REACH: f32 = 6.0 * TILE_SIZE;
+
+pub fn in_reach(ground: Vec2, tile_pos: IVec2) -> bool {
+    let position = tile_position(tile_pos);
+    Vec2::new(position.x, position.y).distance(ground) <= BUILD_REACH
+}
+
+pub fn brush_tiles(brush: Brush, anchor: IVec2) -> Vec<IVec2> {
+    let (width, height) = brush.footprint();
+    (0..height.max(1) as i32)
+        .flat_map(|dy| (0..width.max(1) as i32).map(move |dx| anchor + IVec2::new(dx, dy)))
+        .collect()
+}
+
+// Nothing the player cannot walk on may be put where they stand
+fn blocks_player(tiles: &[IVec2], walkable: bool, ground: Vec2) -> bool {
+    !walkable && player_tiles(ground).any(|tile_pos| tiles.contains(&tile_pos))
+}
+
+pub fn can_build(the_world: &TheWorld, brush: Brush, anchor: IVec2, ground: Vec2) -> bool {
+    if !in_reach(ground, anchor) || blocks_player(&brush_tiles(brush, anchor), brush.is_walkable(), ground) {
+        return false;
+    }
+    match brush {
+        Brush::Tile(tile_type) => the_world.get_tile(anchor).is_some_and(|tile| {
+            tile.tile_type != tile_type && tile.structure.structure_type().is_none()
+        }),
+        Brush::Structure(structure_type) => the_world.can_add_structure(anchor, structure_type),
+    }
+}
+
+pub fn can_afford(inventory: &Inventory, brush: Brush) -> bool {
+    brush.cost().iter().all(|(item, count)| inventory.count(*item) >= *count)
+}
+
+// Takes the cost from the inventory once the brush is down
+pub fn build_with(the_world: &mut TheWorld, inventory: &mut Inventory, brush: Brush, anchor: IVec2) -> bool {
+    if !can_afford(inventory, brush) || !build(the_world, brush, anchor) {
+        return false;
+    }
+    for (item, count) in brush.cost() {
+        inventory.remove(item, count);
+    }
+    true
+}
+
+pub fn build(the_world: &mut TheWorld, brush: Brush, anchor: IVec2) -> bool {
+    match brush {
+        Brush::Tile(tile_type) => the_world.set_tile_type(anchor, tile_type),
+        Brush::Structure(structure_type) => the_world.add_structure(anchor, structure_type),
+    }
+}
+
+// Takes away the structure on a tile, or else turns the tile back into its chunk's biome
+pub fn clear(the_world: &mut TheWorld, tile_pos: IVec2, ground: Vec2) -> bool {
+    if !in_reach(ground, tile_pos) {
+        return false;
+    }
+    if the_world.remove_structure(tile_pos).is_some() {
+        return true;
+    }
+    match the_world.chunk_biome(tile_pos) {
+        Some(biome) if !blocks_player(&[tile_pos], biome.is_walkable(), ground) => {
+            the_world.set_tile_type(tile_pos, biome)
+        }
+        _ => false,
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 pub struct PlayerCamera {}
@@ -8,6 +9,22 @@ pub struct Player {
     pub jump: Jump
 }
 
+// Draws in front of or behind other sorted sprites by the world y of its base
+#[derive(Component)]
+pub struct YSort {
+    // From the translation down to the base, usually the feet
+    pub base_offset: f32,
+    // Airborne sprites draw over every sorted one
+    pub airborne: bool,
+}
+
+// A button reachable with the keyboard and gamepad, visited in ascending order
+#[derive(Component)]
+pub struct Focusable {
+    pub order: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Jump {
     pub is_jumping: bool,
     pub jump_timer: f32,
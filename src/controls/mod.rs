@@ -0,0 +1,19 @@
+use bevy::{input::InputSystem, prelude::*};
+
+pub mod resources;
+mod systems;
+pub mod utils;
+
+use resources::*;
+use systems::*;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ButtonInput<Action>>()
+            .init_resource::<AnalogInput>()
+            .add_systems(Startup, report_binding_conflicts)
+            .add_systems(PreUpdate, (update_actions, update_analog_input).after(InputSystem));
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+pub const BINDING_SLOTS: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Interact,
+    Craft,
+    NextBlock,
+    PreviousBlock,
+    Pause,
+    ZoomIn,
+    ZoomOut,
+    ToggleChunkLines,
+    ToggleFps,
+    ToggleCoords,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Interact,
+        Action::Craft,
+        Action::NextBlock,
+        Action::PreviousBlock,
+        Action::Pause,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ToggleChunkLines,
+        Action::ToggleFps,
+        Action::ToggleCoords,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Jump => "Jump",
+            Action::Interact => "Interact",
+            Action::Craft => "Crafting",
+            Action::NextBlock => "Next Block",
+            Action::PreviousBlock => "Previous Block",
+            Action::Pause => "Pause",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::ToggleChunkLines => "Chunk Lines",
+            Action::ToggleFps => "Show FPS",
+            Action::ToggleCoords => "Show Coordinates",
+        }
+    }
+
+    pub fn gamepad_buttons(&self) -> &'static [GamepadButtonType] {
+        match self {
+            Action::MoveUp => &[GamepadButtonType::DPadUp],
+            Action::MoveDown => &[GamepadButtonType::DPadDown],
+            Action::MoveLeft => &[GamepadButtonType::DPadLeft],
+            Action::MoveRight => &[GamepadButtonType::DPadRight],
+            Action::Jump => &[GamepadButtonType::South],
+            Action::Interact => &[GamepadButtonType::West],
+            Action::Craft => &[GamepadButtonType::North],
+            Action::Pause => &[GamepadButtonType::Start],
+            Action::ZoomIn => &[GamepadButtonType::RightTrigger],
+            Action::ZoomOut => &[GamepadButtonType::LeftTrigger],
+            // Building aims with the mouse, so there is nothing to pick for on a gamepad
+            Action::NextBlock
+            | Action::PreviousBlock
+            | Action::ToggleChunkLines
+            | Action::ToggleFps
+            | Action::ToggleCoords => &[],
+        }
+    }
+
+    fn default_keys(&self) -> [Option<KeyCode>; BINDING_SLOTS] {
+        match self {
+            Action::MoveUp => [Some(KeyCode::KeyW), Some(KeyCode::ArrowUp)],
+            Action::MoveDown => [Some(KeyCode::KeyS), Some(KeyCode::ArrowDown)],
+            Action::MoveLeft => [Some(KeyCode::KeyA), Some(KeyCode::ArrowLeft)],
+            Action::MoveRight => [Some(KeyCode::KeyD), Some(KeyCode::ArrowRight)],
+            Action::Jump => [Some(KeyCode::Space), None],
+            Action::Interact => [Some(KeyCode::KeyE), None],
+            Action::Craft => [Some(KeyCode::KeyC), None],
+            Action::NextBlock => [Some(KeyCode::KeyR), None],
+            Action::PreviousBlock => [Some(KeyCode::KeyQ), None],
+            Action::Pause => [Some(KeyCode::Escape), None],
+            Action::ZoomIn => [Some(KeyCode::Equal), Some(KeyCode::NumpadAdd)],
+            Action::ZoomOut => [Some(KeyCode::Minus), Some(KeyCode::NumpadSubtract)],
+            Action::ToggleChunkLines => [Some(KeyCode::KeyB), None],
+            Action::ToggleFps => [Some(KeyCode::KeyN), None],
+            Action::ToggleCoords => [Some(KeyCode::KeyM), None],
+        }
+    }
+}
+
+// Analog stick and trigger state after the deadzone, in the range -1.0..=1.0.
+// Positive zoom zooms out, matching the mouse wheel.
+#[derive(Resource, Default)]
+pub struct AnalogInput {
+    pub movement: Vec2,
+    pub zoom: f32,
+}
+
+// Saved files only list the actions that existed when they were written, so
+// anything missing is filled in from the defaults when loading.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "HashMap<Action, [Option<KeyCode>; BINDING_SLOTS]>")]
+pub struct KeyBindings {
+    keys: HashMap<Action, [Option<KeyCode>; BINDING_SLOTS]>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            keys: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl From<HashMap<Action, [Option<KeyCode>; BINDING_SLOTS]>> for KeyBindings {
+    fn from(keys: HashMap<Action, [Option<KeyCode>; BINDING_SLOTS]>) -> Self {
+        let mut bindings = KeyBindings::default();
+        bindings.keys.extend(keys);
+        bindings
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> [Option<KeyCode>; BINDING_SLOTS] {
+        self.keys.get(&action).copied().unwrap_or([None; BINDING_SLOTS])
+    }
+
+    pub fn bind(&mut self, action: Action, slot: usize, key: Option<KeyCode>) {
+        self.keys.entry(action).or_insert([None; BINDING_SLOTS])[slot] = key;
+    }
+
+    pub fn pressed(&self, action: Action, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        self.keys(action)
+            .iter()
+            .flatten()
+            .any(|key| keyboard_input.pressed(*key))
+    }
+
+    // Every pair of actions that share a key, in `Action::ALL` order
+    pub fn conflicts(&self) -> Vec<(Action, Action, KeyCode)> {
+        let mut conflicts = vec![];
+        for (index, &action) in Action::ALL.iter().enumerate() {
+            for &other in &Action::ALL[index + 1..] {
+                for key in self.keys(action).iter().flatten() {
+                    if self.keys(other).contains(&Some(*key)) {
+                        conflicts.push((action, other, *key));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn is_conflicting(&self, action: Action, key: KeyCode) -> bool {
+        Action::ALL
+            .iter()
+            .any(|&other| other != action && self.keys(other).contains(&Some(key)))
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::controls::resources::*;
+use crate::controls::utils::*;
+use crate::settings::resources::*;
+
+pub fn update_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ButtonInput<Action>>,
+) {
+    actions.clear();
+    for action in Action::ALL {
+        let gamepad_pressed = gamepads.iter().any(|gamepad| {
+            action
+                .gamepad_buttons()
+                .iter()
+                .any(|button| gamepad_input.pressed(GamepadButton::new(gamepad, *button)))
+        });
+        if gamepad_pressed || settings.key_bindings.pressed(action, &keyboard_input) {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+pub fn update_analog_input(
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    settings: Res<Settings>,
+    mut analog_input: ResMut<AnalogInput>,
+) {
+    let axis = |gamepad: Gamepad, axis_type: GamepadAxisType| -> f32 {
+        gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.0)
+    };
+    let trigger = |gamepad: Gamepad, button_type: GamepadButtonType| -> f32 {
+        gamepad_button_axes
+            .get(GamepadButton::new(gamepad, button_type))
+            .unwrap_or(0.0)
+    };
+
+    let mut movement = Vec2::ZERO;
+    let mut zoom: f32 = 0.0;
+    for gamepad in gamepads.iter() {
+        let stick = apply_deadzone(
+            Vec2::new(
+                axis(gamepad, GamepadAxisType::LeftStickX),
+                axis(gamepad, GamepadAxisType::LeftStickY),
+            ),
+            settings.gamepad_deadzone,
+        );
+        if stick.length() > movement.length() {
+            movement = stick;
+        }
+
+        let gamepad_zoom = -apply_axis_deadzone(
+            axis(gamepad, GamepadAxisType::RightStickY),
+            settings.gamepad_deadzone,
+        ) + apply_axis_deadzone(
+            trigger(gamepad, GamepadButtonType::LeftTrigger2),
+            settings.gamepad_deadzone,
+        ) - apply_axis_deadzone(
+            trigger(gamepad, GamepadButtonType::RightTrigger2),
+            settings.gamepad_deadzone,
+        );
+        if gamepad_zoom.abs() > zoom.abs() {
+            zoom = gamepad_zoom;
+        }
+    }
+
+    analog_input.movement = movement;
+    analog_input.zoom = zoom.clamp(-1.0, 1.0);
+}
+
+pub fn report_binding_conflicts(settings: Res<Settings>) {
+    for (action, other, key) in settings.key_bindings.conflicts() {
+        warn!("{:?} is bound to both {} and {}", key, action.label(), other.label());
+    }
+}
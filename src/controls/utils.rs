@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+pub fn key_label(key: Option<KeyCode>) -> String {
+    match key {
+        Some(key) => {
+            let name = format!("{:?}", key);
+            for prefix in ["Key", "Digit", "Arrow"] {
+                if let Some(stripped) = name.strip_prefix(prefix) {
+                    return stripped.to_string();
+                }
+            }
+            name
+        }
+        None => "-".to_string(),
+    }
+}
+
+// Radial deadzone, rescaled so movement starts from zero at the edge of the deadzone
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+    stick / length * ((length - deadzone) / (1.0 - deadzone)).min(1.0)
+}
+
+pub fn apply_axis_deadzone(value: f32, deadzone: f32) -> f32 {
+    apply_deadzone(Vec2::new(value, 0.0), deadzone).x
+}
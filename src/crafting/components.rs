@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct CraftingMenu {}
+
+#[derive(Component)]
+pub struct RecipeButton {
+    pub index: usize,
+}
+
+#[derive(Component)]
+pub struct RecipeStatusText {
+    pub index: usize,
+}
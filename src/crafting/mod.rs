@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+mod components;
+pub mod recipes;
+mod resources;
+mod systems;
+mod utils;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RecipeBook>()
+            .init_state::<CraftingMenuState>()
+            // Recipes refer to items and structures, whose definitions load during Startup
+            .add_systems(PostStartup, load_recipe_book)
+            .add_systems(OnEnter(CraftingMenuState::Open), spawn_crafting_menu)
+            .add_systems(OnExit(CraftingMenuState::Open), despawn_crafting_menu)
+            .add_systems(OnExit(GameState::Game), close_crafting_menu)
+            .add_systems(
+                Update,
+                (
+                    toggle_crafting_menu,
+                    (interact_recipe_buttons, refresh_crafting_menu)
+                        .chain()
+                        .after(MenuNavigation)
+                        .run_if(in_state(CraftingMenuState::Open)),
+                )
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
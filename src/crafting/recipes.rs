@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::inventory::components::*;
+use crate::inventory::items::*;
+use crate::world::registry::*;
+use crate::world::structures::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Recipe {
+    // Shown in the crafting menu
+    pub name: String,
+    pub inputs: Vec<(ItemType, u32)>,
+    pub outputs: Vec<(ItemType, u32)>,
+    // A structure the player has to stand next to
+    #[serde(default)]
+    pub workstation: Option<StructureType>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CraftError {
+    NeedsWorkstation(StructureType),
+    // How many more of each input are needed
+    MissingInputs(Vec<(ItemType, u32)>),
+    NoRoom,
+}
+
+impl CraftError {
+    pub fn describe(&self) -> String {
+        match self {
+            CraftError::NeedsWorkstation(structure_type) => format!("Needs a {} nearby", structure_type.name()),
+            CraftError::MissingInputs(missing) => {
+                let missing: Vec<String> = missing
+                    .iter()
+                    .map(|(item, count)| format!("{} {}", count, item.definition().label()))
+                    .collect();
+                format!("Missing {}", missing.join(", "))
+            }
+            CraftError::NoRoom => "Inventory full".to_string(),
+        }
+    }
+}
+
+impl Recipe {
+    // Inputs with repeated items merged, in the order they are first listed
+    pub fn required(&self) -> Vec<(ItemType, u32)> {
+        let mut required: Vec<(ItemType, u32)> = vec![];
+        for (item, count) in &self.inputs {
+            match required.iter_mut().find(|(required_item, _)| required_item == item) {
+                Some((_, required_count)) => *required_count += count,
+                None => required.push((*item, *count)),
+            }
+        }
+        required
+    }
+
+    // Rejects recipes that could never be crafted, whatever the inventory holds
+    pub fn validate(&self) -> Result<(), String> {
+        if self.inputs.is_empty() {
+            return Err("costs nothing".to_string());
+        }
+        if self.outputs.is_empty() {
+            return Err("produces nothing".to_string());
+        }
+        if self.inputs.iter().chain(&self.outputs).any(|(_, count)| *count == 0) {
+            return Err("lists an item with a count of zero".to_string());
+        }
+        for (item, count) in self.required() {
+            if count > INVENTORY_SLOTS as u32 * item.max_stack() {
+                return Err(format!("needs more {} than an inventory holds", item.name()));
+            }
+        }
+        if !Inventory::default().can_add(&self.outputs) {
+            return Err("produces more than an inventory holds".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn check(&self, inventory: &Inventory, nearby: &[StructureType]) -> Result<(), CraftError> {
+        self.resolve(inventory, nearby).map(|_| ())
+    }
+
+    // Either consumes every input and adds every output, or leaves the inventory untouched
+    pub fn craft(&self, inventory: &mut Inventory, nearby: &[StructureType]) -> Result<(), CraftError> {
+        *inventory = self.resolve(inventory, nearby)?;
+        Ok(())
+    }
+
+    fn resolve(&self, inventory: &Inventory, nearby: &[StructureType]) -> Result<Inventory, CraftError> {
+        if let Some(workstation) = self.workstation {
+            if !nearby.contains(&workstation) {
+                return Err(CraftError::NeedsWorkstation(workstation));
+            }
+        }
+
+        let required = self.required();
+        let missing: Vec<(ItemType, u32)> = required
+            .iter()
+            .filter_map(|(item, count)| {
+                let have = inventory.count(*item);
+                (have < *count).then(|| (*item, count - have))
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(CraftError::MissingInputs(missing));
+        }
+
+        // Inputs are taken out first so the slots they free can hold the outputs
+        let mut crafted = inventory.clone();
+        for (item, count) in required {
+            crafted.remove(item, count);
+        }
+        for (item, count) in &self.outputs {
+            if crafted.add(*item, *count) > 0 {
+                return Err(CraftError::NoRoom);
+            }
+        }
+        Ok(crafted)
+    }
+
+    fn undefined_names(&self) -> Vec<&'static str> {
+        let items = self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .filter(|(item, _)| !item.is_defined())
+            .map(|(item, _)| item.name());
+        let workstation = self
+            .workstation
+            .filter(|structure_type| !structure_type.is_defined())
+            .map(|structure_type| structure_type.name());
+        items.chain(workstation).collect()
+    }
+}
+
+pub fn recipes_path() -> PathBuf {
+    PathBuf::from("assets/recipes.ron")
+}
+
+// Loads after the item and structure definitions. Recipes that are impossible or
+// mention undefined types are left out, each with the reason why.
+pub fn load_recipes() -> Result<(Vec<Recipe>, Vec<String>), String> {
+    let loaded: Vec<Recipe> = load_definitions(&recipes_path())?;
+    let mut recipes: Vec<Recipe> = vec![];
+    let mut rejected: Vec<String> = vec![];
+    for recipe in loaded {
+        let undefined = recipe.undefined_names();
+        let result = if recipes.iter().any(|known| known.name == recipe.name) {
+            Err("is defined twice".to_string())
+        } else if !undefined.is_empty() {
+            Err(format!("mentions undefined {}", undefined.join(", ")))
+        } else {
+            recipe.validate()
+        };
+        match result {
+            Ok(()) => recipes.push(recipe),
+            Err(reason) => rejected.push(format!("{} {}", recipe.name, reason)),
+        }
+    }
+    Ok((recipes, rejected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Items and structures without a definition stack up to 99, which is all these need
+    fn item(name: &str) -> ItemType {
+        ItemType::named(name)
+    }
+
+    fn recipe(inputs: &[(&str, u32)], outputs: &[(&str, u32)], workstation: Option<&str>) -> Recipe {
+        Recipe {
+            name: "Test".to_string(),
+            inputs: inputs.iter().map(|(name, count)| (item(name), *count)).collect(),
+            outputs: outputs.iter().map(|(name, count)| (item(name), *count)).collect(),
+            workstation: workstation.map(StructureType::named),
+        }
+    }
+
+    fn inventory(stacks: &[(&str, u32)]) -> Inventory {
+        let mut inventory = Inventory::default();
+        for (name, count) in stacks {
+            inventory.add(item(name), *count);
+        }
+        inventory
+    }
+
+    #[test]
+    fn crafting_consumes_inputs_and_adds_outputs() {
+        let recipe = recipe(&[("TestLog", 2), ("TestNail", 1)], &[("TestBoard", 3)], None);
+        let mut inventory = inventory(&[("TestLog", 5), ("TestNail", 1)]);
+        assert_eq!(recipe.craft(&mut inventory, &[]), Ok(()));
+        assert_eq!(inventory.count(item("TestLog")), 3);
+        assert_eq!(inventory.count(item("TestNail")), 0);
+        assert_eq!(inventory.count(item("TestBoard")), 3);
+    }
+
+    #[test]
+    fn missing_inputs_are_reported_and_nothing_changes() {
+        let recipe = recipe(&[("TestLog", 4), ("TestNail", 2)], &[("TestBoard", 1)], None);
+        let mut inventory = inventory(&[("TestLog", 1), ("TestNail", 2)]);
+        let before = inventory.clone();
+        assert_eq!(
+            recipe.craft(&mut inventory, &[]),
+            Err(CraftError::MissingInputs(vec![(item("TestLog"), 3)]))
+        );
+        assert_eq!(inventory, before);
+    }
+
+    #[test]
+    fn repeated_inputs_are_counted_together() {
+        let recipe = recipe(&[("TestLog", 2), ("TestLog", 2)], &[("TestBoard", 1)], None);
+        assert_eq!(recipe.required(), vec![(item("TestLog"), 4)]);
+        assert_eq!(
+            recipe.check(&inventory(&[("TestLog", 3)]), &[]),
+            Err(CraftError::MissingInputs(vec![(item("TestLog"), 1)]))
+        );
+        assert_eq!(recipe.check(&inventory(&[("TestLog", 4)]), &[]), Ok(()));
+    }
+
+    #[test]
+    fn workstation_has_to_be_nearby() {
+        let recipe = recipe(&[("TestLog", 1)], &[("TestBoard", 1)], Some("TestBench"));
+        let inventory = inventory(&[("TestLog", 1)]);
+        assert_eq!(
+            recipe.check(&inventory, &[StructureType::named("TestRock")]),
+            Err(CraftError::NeedsWorkstation(StructureType::named("TestBench")))
+        );
+        assert_eq!(recipe.check(&inventory, &[StructureType::named("TestBench")]), Ok(()));
+    }
+
+    #[test]
+    fn outputs_need_room_after_inputs_are_taken() {
+        let names: Vec<String> = (0..INVENTORY_SLOTS).map(|index| format!("TestFiller{}", index)).collect();
+        let full: Vec<(&str, u32)> = names.iter().map(|name| (name.as_str(), 1)).collect();
+        let mut inventory = inventory(&full);
+
+        // Taking the only filler frees its slot for the output
+        let swap = recipe(&[(full[0].0, 1)], &[("TestBoard", 1)], None);
+        assert_eq!(swap.check(&inventory, &[]), Ok(()));
+
+        let no_room = recipe(&[(full[0].0, 1)], &[("TestBoard", 1), ("TestNail", 1)], None);
+        let before = inventory.clone();
+        assert_eq!(no_room.craft(&mut inventory, &[]), Err(CraftError::NoRoom));
+        assert_eq!(inventory, before);
+    }
+
+    #[test]
+    fn impossible_recipes_fail_validation() {
+        assert!(recipe(&[("TestLog", 1)], &[("TestBoard", 1)], None).validate().is_ok());
+        assert!(recipe(&[], &[("TestBoard", 1)], None).validate().is_err());
+        assert!(recipe(&[("TestLog", 1)], &[], None).validate().is_err());
+        assert!(recipe(&[("TestLog", 0)], &[("TestBoard", 1)], None).validate().is_err());
+        assert!(recipe(&[("TestLog", 1)], &[("TestBoard", 0)], None).validate().is_err());
+        assert!(recipe(&[("TestLog", 99 * INVENTORY_SLOTS as u32 + 1)], &[("TestBoard", 1)], None)
+            .validate()
+            .is_err());
+        assert!(recipe(&[("TestLog", 1)], &[("TestBoard", 99 * INVENTORY_SLOTS as u32 + 1)], None)
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+
+use crate::crafting::recipes::*;
+
+#[derive(Resource, Default)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::controls::resources::*;
+use crate::crafting::components::*;
+use crate::crafting::recipes::*;
+use crate::crafting::resources::*;
+use crate::crafting::utils::*;
+use crate::inventory::components::*;
+use crate::menu::styles::*;
+use crate::resources::*;
+use crate::world::resources::*;
+
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const READY_COLOR: Color = Color::rgb(0.55, 0.9, 0.55);
+const BLOCKED_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+
+pub fn load_recipe_book(mut recipe_book: ResMut<RecipeBook>) {
+    match load_recipes() {
+        Ok((recipes, rejected)) => {
+            for reason in rejected {
+                warn!("Skipping recipe {}", reason);
+            }
+            recipe_book.recipes = recipes;
+        }
+        Err(err) => error!("Failed to load recipes: {}", err),
+    }
+}
+
+pub fn toggle_crafting_menu(
+    actions: Res<ButtonInput<Action>>,
+    crafting_menu_state: Res<State<CraftingMenuState>>,
+    mut next_crafting_menu_state: ResMut<NextState<CraftingMenuState>>,
+) {
+    match *crafting_menu_state.get() {
+        CraftingMenuState::Closed if actions.just_pressed(Action::Craft) => {
+            next_crafting_menu_state.set(CraftingMenuState::Open);
+        }
+        CraftingMenuState::Open if actions.any_just_pressed([Action::Craft, Action::Pause]) => {
+            next_crafting_menu_state.set(CraftingMenuState::Closed);
+        }
+        _ => {}
+    }
+}
+
+pub fn close_crafting_menu(mut next_crafting_menu_state: ResMut<NextState<CraftingMenuState>>) {
+    next_crafting_menu_state.set(CraftingMenuState::Closed);
+}
+
+pub fn spawn_crafting_menu(mut commands: Commands, asset_server: Res<AssetServer>, recipe_book: Res<RecipeBook>) {
+    build_crafting_menu(&mut commands, &asset_server, &recipe_book);
+}
+
+pub fn despawn_crafting_menu(mut commands: Commands, menu_query: Query<Entity, With<CraftingMenu>>) {
+    for menu_entity in menu_query.iter() {
+        commands.entity(menu_entity).despawn_recursive();
+    }
+}
+
+pub fn refresh_crafting_menu(
+    the_world: Res<TheWorld>,
+    recipe_book: Res<RecipeBook>,
+    player_query: Query<(&Transform, &Inventory), With<Player>>,
+    mut text_query: Query<(&RecipeStatusText, &mut Text)>,
+) {
+    let Ok((transform, inventory)) = player_query.get_single() else {
+        return;
+    };
+    let nearby = nearby_structures(&the_world, transform.translation.truncate());
+    for (status_text, mut text) in text_query.iter_mut() {
+        let Some(recipe) = recipe_book.recipes.get(status_text.index) else {
+            continue;
+        };
+        let (value, color) = match recipe.check(inventory, &nearby) {
+            Ok(()) => ("Ready".to_string(), READY_COLOR),
+            Err(err) => (err.describe(), BLOCKED_COLOR),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+pub fn interact_recipe_buttons(
+    mut button_query: Query<(&Interaction, &mut BackgroundColor, &RecipeButton), Changed<Interaction>>,
+    the_world: Res<TheWorld>,
+    recipe_book: Res<RecipeBook>,
+    mut player_query: Query<(&Transform, &mut Inventory), With<Player>>,
+) {
+    for (interaction, mut background_color, recipe_button) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+
+                let (Ok((transform, mut inventory)), Some(recipe)) =
+                    (player_query.get_single_mut(), recipe_book.recipes.get(recipe_button.index))
+                else {
+                    continue;
+                };
+                let nearby = nearby_structures(&the_world, transform.translation.truncate());
+                match recipe.craft(&mut inventory, &nearby) {
+                    Ok(()) => info!("Crafted {}", recipe.name),
+                    Err(err) => info!("Cannot craft {}: {}", recipe.name, err.describe()),
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn build_crafting_menu(commands: &mut Commands, asset_server: &Res<AssetServer>, recipe_book: &RecipeBook) {
+    let small_text_style = TextStyle {
+        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            CraftingMenu {},
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(8.0),
+                        padding: UiRect::all(Val::Px(16.0)),
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Crafting", get_button_text_style(asset_server)));
+                    if recipe_book.recipes.is_empty() {
+                        parent.spawn(TextBundle::from_section("Nothing to craft", small_text_style.clone()));
+                    }
+                    for (index, recipe) in recipe_book.recipes.iter().enumerate() {
+                        let icon = recipe.outputs.first().and_then(|(item, _)| item.definition().icon);
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(440.0),
+                                        flex_direction: FlexDirection::Row,
+                                        align_items: AlignItems::Center,
+                                        column_gap: Val::Px(10.0),
+                                        padding: UiRect::all(Val::Px(6.0)),
+                                        ..default()
+                                    },
+                                    background_color: NORMAL_BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                                RecipeButton { index },
+                                Focusable { order: index },
+                            ))
+                            .with_children(|parent| {
+                                let mut icon_bundle = ImageBundle {
+                                    style: Style {
+                                        width: Val::Px(40.0),
+                                        height: Val::Px(40.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                };
+                                match icon {
+                                    Some(icon) => icon_bundle.image = asset_server.load(icon).into(),
+                                    None => icon_bundle.visibility = Visibility::Hidden,
+                                }
+                                parent.spawn(icon_bundle);
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            flex_direction: FlexDirection::Column,
+                                            ..default()
+                                        },
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            recipe.name.clone(),
+                                            get_button_text_style(asset_server),
+                                        ));
+                                        parent.spawn(TextBundle::from_section(
+                                            format!("{} -> {}", item_list(&recipe.inputs), item_list(&recipe.outputs)),
+                                            small_text_style.clone(),
+                                        ));
+                                        parent.spawn((
+                                            TextBundle::from_section("", small_text_style.clone()),
+                                            RecipeStatusText { index },
+                                        ));
+                                    });
+                            });
+                    }
+                });
+        });
+}
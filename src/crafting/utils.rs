@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use crate::inventory::items::*;
+use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::utils::*;
+
+// Tiles from the player to a workstation that still count as standing next to it
+const WORKSTATION_RANGE: i32 = 2;
+
+pub fn nearby_structures(the_world: &TheWorld, ground: Vec2) -> Vec<StructureType> {
+    let origin = world_to_tile(ground);
+    let mut nearby: Vec<StructureType> = vec![];
+    for y in -WORKSTATION_RANGE..=WORKSTATION_RANGE {
+        for x in -WORKSTATION_RANGE..=WORKSTATION_RANGE {
+            if let Some((_, structure_type)) = the_world.structure_at(origin + IVec2::new(x, y)) {
+                if !nearby.contains(&structure_type) {
+                    nearby.push(structure_type);
+                }
+            }
+        }
+    }
+    nearby
+}
+
+pub fn item_list(items: &[(ItemType, u32)]) -> String {
+    items
+        .iter()
+        .map(|(item, count)| format!("{} {}", count, item.definition().label()))
+        .collect::<Vec<String>>()
+        .join(" + ")
+}
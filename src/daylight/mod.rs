@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+mod components;
+pub mod resources;
+mod systems;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct DaylightPlugin;
+
+impl Plugin for DaylightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldClock>()
+            .add_systems(OnEnter(GameState::Game), (reset_world_clock, spawn_clock_hud))
+            .add_systems(OnEnter(GameState::Menu), despawn_clock_hud)
+            .add_systems(OnEnter(GameState::Editor), clear_daylight)
+            .add_systems(
+                Update,
+                (advance_world_clock, apply_daylight, refresh_clock_hud)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// New games start in the morning
+const START_TIME: f32 = 8.0 / 24.0;
+
+// Light colour at each hour, blended between neighbours and wrapping past midnight
+const LIGHT_KEYS: [(f32, [f32; 3]); 6] = [
+    (4.0, [0.25, 0.3, 0.5]),
+    (7.0, [1.0, 0.8, 0.7]),
+    (9.0, [1.0, 1.0, 1.0]),
+    (17.0, [1.0, 1.0, 1.0]),
+    (19.5, [1.0, 0.7, 0.55]),
+    (22.0, [0.25, 0.3, 0.5]),
+];
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WorldClock {
+    pub day: u32,
+    // Fraction of the day gone, midnight is 0.0
+    pub time: f32,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        WorldClock {
+            day: 1,
+            time: START_TIME,
+        }
+    }
+}
+
+impl WorldClock {
+    pub fn advance(&mut self, seconds: f32, day_length: f32) {
+        self.time += seconds / day_length;
+        while self.time >= 1.0 {
+            self.time -= 1.0;
+            self.day += 1;
+        }
+    }
+
+    pub fn hours(&self) -> f32 {
+        self.time * 24.0
+    }
+
+    pub fn label(&self) -> String {
+        let minutes = (self.time * 24.0 * 60.0) as u32;
+        format!("Day {} {:02}:{:02}", self.day, minutes / 60 % 24, minutes % 60)
+    }
+
+    pub fn tint(&self) -> Color {
+        let hours = self.hours();
+        let next = LIGHT_KEYS
+            .iter()
+            .position(|(hour, _)| *hour > hours)
+            .unwrap_or(0);
+        let previous = (next + LIGHT_KEYS.len() - 1) % LIGHT_KEYS.len();
+        let (from_hour, from) = LIGHT_KEYS[previous];
+        let (to_hour, to) = LIGHT_KEYS[next];
+        let span = (to_hour - from_hour).rem_euclid(24.0);
+        let progress = if span > 0.0 {
+            (hours - from_hour).rem_euclid(24.0) / span
+        } else {
+            0.0
+        };
+        let channel = |index: usize| from[index] + (to[index] - from[index]) * progress;
+        Color::rgb(channel(0), channel(1), channel(2))
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::daylight::components::*;
+use crate::daylight::resources::*;
+use crate::resources::*;
+use crate::save::resources::*;
+use crate::settings::resources::*;
+use crate::world::resources::*;
+
+pub fn reset_world_clock(
+    mut reader: EventReader<GameStart>,
+    mut clock: ResMut<WorldClock>,
+    pending_load: Option<Res<PendingLoad>>,
+) {
+    if let Some(_game_start) = reader.read().last() {
+        *clock = pending_load
+            .map(|pending_load| pending_load.save.clock)
+            .unwrap_or_default();
+    }
+}
+
+pub fn advance_world_clock(mut clock: ResMut<WorldClock>, settings: Res<Settings>, time: Res<Time>) {
+    clock.advance(time.delta_seconds(), settings.day_length());
+}
+
+// Tiles share the atlas material, sorted sprites are the player and everything standing in the world
+pub fn apply_daylight(
+    clock: Res<WorldClock>,
+    tile_atlas: Option<Res<TileAtlas>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprite_query: Query<&mut Sprite, With<YSort>>,
+) {
+    set_tint(clock.tint(), tile_atlas, &mut materials, &mut sprite_query);
+}
+
+// The editor always shows the world in full daylight
+pub fn clear_daylight(
+    tile_atlas: Option<Res<TileAtlas>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprite_query: Query<&mut Sprite, With<YSort>>,
+) {
+    set_tint(Color::WHITE, tile_atlas, &mut materials, &mut sprite_query);
+}
+
+pub fn spawn_clock_hud(mut commands: Commands, asset_server: Res<AssetServer>, mut reader: EventReader<GameStart>) {
+    if let Some(_game_start) = reader.read().last() {
+        commands.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    // Sits under the coordinates overlay
+                    left: Val::Px(15.0),
+                    top: Val::Px(50.0),
+                    ..default()
+                },
+                ..default()
+            },
+            ClockText {},
+        ));
+    }
+}
+
+pub fn despawn_clock_hud(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    clock_query: Query<Entity, With<ClockText>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        for clock_entity in clock_query.iter() {
+            commands.entity(clock_entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn refresh_clock_hud(clock: Res<WorldClock>, mut text_query: Query<&mut Text, With<ClockText>>) {
+    let label = clock.label();
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != label {
+            text.sections[0].value = label.clone();
+        }
+    }
+}
+
+fn set_tint(
+    tint: Color,
+    tile_atlas: Option<Res<TileAtlas>>,
+    materials: &mut Assets<ColorMaterial>,
+    sprite_query: &mut Query<&mut Sprite, With<YSort>>,
+) {
+    if let Some(tile_atlas) = tile_atlas {
+        if materials.get(&tile_atlas.material).is_some_and(|material| material.color != tint) {
+            if let Some(material) = materials.get_mut(&tile_atlas.material) {
+                material.color = tint;
+            }
+        }
+    }
+    for mut sprite in sprite_query.iter_mut() {
+        if sprite.color != tint {
+            sprite.color = tint;
+        }
+    }
+}
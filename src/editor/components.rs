@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+use crate::editor::resources::*;
+
+#[derive(Component)]
+pub struct EditorPanel {}
+
+#[derive(Component)]
+pub struct PaletteButton {
+    pub index: usize,
+}
+
+#[derive(Component)]
+pub struct ToolButton {
+    pub tool: EditorTool,
+}
+
+#[derive(Component)]
+pub struct EditorStatusText {}
+
+#[derive(Component)]
+pub struct EditorCursor {}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+
+mod components;
+mod resources;
+mod systems;
+mod utils;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorTools>()
+            .init_resource::<EditHistory>()
+            .add_systems(OnEnter(GameState::Editor), (setup_editor, spawn_editor).chain())
+            .add_systems(OnExit(GameState::Editor), despawn_editor)
+            .add_systems(
+                Update,
+                (
+                    exit_editor,
+                    move_editor_camera,
+                    (
+                        interact_editor_buttons,
+                        editor_shortcuts,
+                        edit_with_mouse,
+                        show_editor_cursor,
+                        refresh_editor_panel,
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Editor)),
+            );
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use crate::building::resources::*;
+use crate::world::components::*;
+use crate::world::resources::*;
+
+// Older strokes are dropped past this many
+const MAX_UNDO_STEPS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditorTool {
+    #[default]
+    Paint,
+    Rectangle,
+    Fill,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 3] = [EditorTool::Paint, EditorTool::Rectangle, EditorTool::Fill];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EditorTool::Paint => "Paint",
+            EditorTool::Rectangle => "Rectangle",
+            EditorTool::Fill => "Fill",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct EditorTools {
+    pub palette: BuildPalette,
+    pub tool: EditorTool,
+    // Tile a rectangle was started on, and whether it erases
+    pub drag_start: Option<(IVec2, bool)>,
+    pub status: String,
+    // Set once leaving with unsaved changes was warned about, the next Esc leaves anyway
+    pub confirm_exit: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct TileEdit {
+    pub tile_pos: IVec2,
+    pub before: Tile,
+    pub after: Tile,
+}
+
+// Edits gather in the current stroke, which becomes a single undo step once the mouse is released
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<Vec<TileEdit>>,
+    redo: Vec<Vec<TileEdit>>,
+    stroke: Vec<TileEdit>,
+    // Whether the map changed since it was loaded or last exported
+    unsaved: bool,
+}
+
+impl EditHistory {
+    // Runs an edit that touches at most the given tiles and keeps whatever it changed
+    pub fn record(&mut self, the_world: &mut TheWorld, tiles: &[IVec2], edit: impl FnOnce(&mut TheWorld)) {
+        let mut before: Vec<(IVec2, Tile)> = vec![];
+        for tile_pos in tiles {
+            if let Some(tile) = the_world.get_tile(*tile_pos) {
+                if !before.iter().any(|(known, _)| known == tile_pos) {
+                    before.push((*tile_pos, *tile));
+                }
+            }
+        }
+        edit(the_world);
+        for (tile_pos, before) in before {
+            if let Some(after) = the_world.get_tile(tile_pos).copied().filter(|after| *after != before) {
+                self.stroke.push(TileEdit { tile_pos, before, after });
+            }
+        }
+    }
+
+    pub fn finish_stroke(&mut self) {
+        if self.stroke.is_empty() {
+            return;
+        }
+        self.undo.push(std::mem::take(&mut self.stroke));
+        self.unsaved = true;
+        if self.undo.len() > MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, the_world: &mut TheWorld) -> bool {
+        self.finish_stroke();
+        let Some(stroke) = self.undo.pop() else {
+            return false;
+        };
+        for edit in stroke.iter().rev() {
+            the_world.set_tile(edit.tile_pos, edit.before);
+        }
+        self.redo.push(stroke);
+        self.unsaved = true;
+        true
+    }
+
+    pub fn redo(&mut self, the_world: &mut TheWorld) -> bool {
+        let Some(stroke) = self.redo.pop() else {
+            return false;
+        };
+        for edit in stroke.iter() {
+            the_world.set_tile(edit.tile_pos, edit.after);
+        }
+        self.undo.push(stroke);
+        self.unsaved = true;
+        true
+    }
+
+    pub fn has_unsaved(&self) -> bool {
+        self.unsaved || !self.stroke.is_empty()
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.finish_stroke();
+        self.unsaved = false;
+    }
+
+    pub fn clear(&mut self) {
+        *self = EditHistory::default();
+    }
+}
@@ -0,0 +1,426 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::building::resources::*;
+use crate::components::*;
+use crate::controls::resources::*;
+use crate::editor::components::*;
+use crate::editor::resources::*;
+use crate::editor::utils::*;
+use crate::menu::styles::*;
+use crate::resources::*;
+use crate::save::resources::*;
+use crate::save::utils::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+const CAMERA_SPEED: f32 = 800.0;
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const CURSOR_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const ERASE_CURSOR_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.3);
+
+pub fn setup_editor(
+    mut the_world: ResMut<TheWorld>,
+    mut tools: ResMut<EditorTools>,
+    mut history: ResMut<EditHistory>,
+    mut chunk_line_render_state: ResMut<NextState<ChunkLineRenderState>>,
+    mut camera_query: Query<&mut Transform, With<PlayerCamera>>,
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
+) {
+    let path = editor_map_path(&world_map);
+    // Picks up where the last export left off, or starts from the selected seed
+    let status = if path.is_file() {
+        match read_map(&path) {
+            Ok(map) => {
+                map.restore(&mut the_world);
+                format!("Editing {}", path.display())
+            }
+            Err(err) => {
+                error!("Failed to load the map {}", err);
+                the_world.reset(world_seed.seed, world_generator.kind);
+                format!("Failed to load {}", path.display())
+            }
+        }
+    } else {
+        the_world.reset(world_seed.seed, world_generator.kind);
+        format!("New map, seed {}", world_seed.seed)
+    };
+
+    *tools = EditorTools {
+        palette: BuildPalette::from_definitions(),
+        status,
+        ..default()
+    };
+    history.clear();
+    // The chunk outlines double as a grid while editing
+    chunk_line_render_state.set(ChunkLineRenderState::On);
+    for mut camera_transform in camera_query.iter_mut() {
+        camera_transform.translation.x = 0.0;
+        camera_transform.translation.y = 0.0;
+    }
+}
+
+pub fn spawn_editor(mut commands: Commands, asset_server: Res<AssetServer>, tools: Res<EditorTools>) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: CURSOR_COLOR,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, OVERLAY_Z),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        EditorCursor {},
+    ));
+    build_editor_panel(&mut commands, &asset_server, &tools);
+}
+
+pub fn despawn_editor(
+    mut commands: Commands,
+    mut chunk_line_render_state: ResMut<NextState<ChunkLineRenderState>>,
+    editor_query: Query<Entity, Or<(With<EditorPanel>, With<EditorCursor>)>>,
+) {
+    for editor_entity in editor_query.iter() {
+        commands.entity(editor_entity).despawn_recursive();
+    }
+    chunk_line_render_state.set(ChunkLineRenderState::Off);
+}
+
+pub fn exit_editor(
+    actions: Res<ButtonInput<Action>>,
+    history: Res<EditHistory>,
+    mut tools: ResMut<EditorTools>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut game_over_event_writer: EventWriter<GameOver>,
+) {
+    // Editing again after the warning needs another warning
+    if history.is_changed() {
+        tools.confirm_exit = false;
+    }
+    if actions.just_pressed(Action::Pause) {
+        if history.has_unsaved() && !tools.confirm_exit {
+            tools.confirm_exit = true;
+            tools.status = "Unsaved changes, Ctrl+S to export or Esc again to leave".to_string();
+            return;
+        }
+        game_state.set(GameState::Menu);
+        game_over_event_writer.send(GameOver {});
+    }
+}
+
+pub fn move_editor_camera(
+    actions: Res<ButtonInput<Action>>,
+    analog_input: Res<AnalogInput>,
+    time: Res<Time>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<PlayerCamera>>,
+) {
+    let mut direction = Vec2::ZERO;
+    for (action, step) in [
+        (Action::MoveLeft, Vec2::NEG_X),
+        (Action::MoveRight, Vec2::X),
+        (Action::MoveUp, Vec2::Y),
+        (Action::MoveDown, Vec2::NEG_Y),
+    ] {
+        if actions.pressed(action) {
+            direction += step;
+        }
+    }
+    let direction = if direction == Vec2::ZERO {
+        analog_input.movement
+    } else {
+        direction.normalize()
+    };
+
+    for (mut transform, projection) in camera_query.iter_mut() {
+        // Zoomed out the camera covers more ground per second, so it feels the same at every zoom
+        let movement = direction * CAMERA_SPEED * projection.scale * time.delta_seconds();
+        transform.translation += movement.extend(0.0);
+    }
+}
+
+pub fn editor_shortcuts(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
+    mut the_world: ResMut<TheWorld>,
+    mut tools: ResMut<EditorTools>,
+    mut history: ResMut<EditHistory>,
+    world_map: Res<WorldMap>,
+) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if ctrl && keyboard_input.just_pressed(KeyCode::KeyZ) && !shift {
+        if !history.undo(&mut the_world) {
+            tools.status = "Nothing to undo".to_string();
+        }
+    } else if ctrl && (keyboard_input.just_pressed(KeyCode::KeyY) || shift && keyboard_input.just_pressed(KeyCode::KeyZ)) {
+        if !history.redo(&mut the_world) {
+            tools.status = "Nothing to redo".to_string();
+        }
+    } else if ctrl && keyboard_input.just_pressed(KeyCode::KeyS) {
+        let path = editor_map_path(&world_map);
+        tools.status = match write_map(&path, &SavedWorld::from(&*the_world)) {
+            Ok(()) => {
+                history.mark_saved();
+                format!("Exported to {}", path.display())
+            }
+            Err(err) => {
+                error!("Failed to export the map to {}: {}", path.display(), err);
+                format!("Failed to export to {}", path.display())
+            }
+        };
+    }
+
+    for (key, tool) in [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3].into_iter().zip(EditorTool::ALL) {
+        if keyboard_input.just_pressed(key) {
+            tools.tool = tool;
+            tools.drag_start = None;
+        }
+    }
+    if actions.just_pressed(Action::NextBlock) {
+        tools.palette.cycle(1);
+    }
+    if actions.just_pressed(Action::PreviousBlock) {
+        tools.palette.cycle(-1);
+    }
+}
+
+pub fn interact_editor_buttons(
+    palette_query: Query<(&Interaction, &PaletteButton), Changed<Interaction>>,
+    tool_query: Query<(&Interaction, &ToolButton), Changed<Interaction>>,
+    mut tools: ResMut<EditorTools>,
+) {
+    for (interaction, palette_button) in palette_query.iter() {
+        if *interaction == Interaction::Pressed {
+            tools.palette.selected = palette_button.index;
+        }
+    }
+    for (interaction, tool_button) in tool_query.iter() {
+        if *interaction == Interaction::Pressed {
+            tools.tool = tool_button.tool;
+            tools.drag_start = None;
+        }
+    }
+}
+
+// Left click places the selected brush with the current tool, right click erases structures with it
+pub fn edit_with_mouse(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    panel_query: Query<&Interaction, Or<(With<EditorPanel>, With<PaletteButton>, With<ToolButton>)>>,
+    mut the_world: ResMut<TheWorld>,
+    mut tools: ResMut<EditorTools>,
+    mut history: ResMut<EditHistory>,
+) {
+    let released = mouse_input.any_just_released([MouseButton::Left, MouseButton::Right]);
+    let over_panel = panel_query.iter().any(|interaction| *interaction != Interaction::None);
+    let tile_pos = cursor_tile(&window_query, &camera_query).filter(|_| !over_panel);
+
+    if let Some(tile_pos) = tile_pos {
+        let erasing = mouse_input.pressed(MouseButton::Right);
+        let brush = if erasing { None } else { tools.palette.brush() };
+        let started = mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]);
+        match tools.tool {
+            EditorTool::Paint => {
+                if mouse_input.any_pressed([MouseButton::Left, MouseButton::Right]) {
+                    edit_area(&mut the_world, &mut history, brush, &[tile_pos]);
+                }
+            }
+            EditorTool::Rectangle => {
+                if started {
+                    tools.drag_start = Some((tile_pos, erasing));
+                }
+                if released {
+                    if let Some((from, erasing)) = tools.drag_start.take() {
+                        let brush = if erasing { None } else { tools.palette.brush() };
+                        edit_area(&mut the_world, &mut history, brush, &rectangle(from, tile_pos));
+                    }
+                }
+            }
+            EditorTool::Fill => {
+                if started {
+                    let region = flood_region(&the_world, tile_pos);
+                    edit_area(&mut the_world, &mut history, brush, &region);
+                }
+            }
+        }
+    }
+
+    if released {
+        // A rectangle let go of over the panel is dropped
+        tools.drag_start = None;
+        history.finish_stroke();
+    }
+}
+
+pub fn show_editor_cursor(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    panel_query: Query<&Interaction, Or<(With<EditorPanel>, With<PaletteButton>, With<ToolButton>)>>,
+    tools: Res<EditorTools>,
+    mut cursor_query: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<EditorCursor>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = cursor_query.get_single_mut() else {
+        return;
+    };
+    let over_panel = panel_query.iter().any(|interaction| *interaction != Interaction::None);
+    let Some(tile_pos) = cursor_tile(&window_query, &camera_query).filter(|_| !over_panel) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    // A rectangle being dragged is shown whole, otherwise the brush footprint
+    let (min, max, erasing) = match tools.drag_start {
+        Some((from, erasing)) => (from.min(tile_pos), from.max(tile_pos), erasing),
+        None => {
+            let (width, height) = tools.palette.brush().map(|brush| brush.footprint()).unwrap_or((1, 1));
+            let size = IVec2::new(width.max(1) as i32, height.max(1) as i32);
+            (tile_pos, tile_pos + size - IVec2::ONE, false)
+        }
+    };
+    let size = (max - min + IVec2::ONE).as_vec2() * TILE_SIZE;
+    let corner = tile_position(min);
+    transform.translation.x = corner.x - TILE_SIZE / 2.0 + size.x / 2.0;
+    transform.translation.y = corner.y - TILE_SIZE / 2.0 + size.y / 2.0;
+    sprite.custom_size = Some(size);
+    sprite.color = if erasing { ERASE_CURSOR_COLOR } else { CURSOR_COLOR };
+    *visibility = Visibility::Inherited;
+}
+
+pub fn refresh_editor_panel(
+    tools: Res<EditorTools>,
+    mut palette_query: Query<(&Interaction, &PaletteButton, &mut BackgroundColor), Without<ToolButton>>,
+    mut tool_query: Query<(&Interaction, &ToolButton, &mut BackgroundColor), Without<PaletteButton>>,
+    mut status_query: Query<&mut Text, With<EditorStatusText>>,
+) {
+    let button_color = |interaction: &Interaction, selected: bool| match (interaction, selected) {
+        (_, true) | (Interaction::Pressed, _) => PRESSED_BUTTON_COLOR,
+        (Interaction::Hovered, _) => HOVERED_BUTTON_COLOR,
+        (Interaction::None, _) => NORMAL_BUTTON_COLOR,
+    };
+    for (interaction, palette_button, mut background_color) in palette_query.iter_mut() {
+        let color = button_color(interaction, palette_button.index == tools.palette.selected);
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+    for (interaction, tool_button, mut background_color) in tool_query.iter_mut() {
+        let color = button_color(interaction, tool_button.tool == tools.tool);
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+    if tools.is_changed() {
+        for mut text in status_query.iter_mut() {
+            text.sections[0].value = tools.status.clone();
+        }
+    }
+}
+
+fn cursor_tile(
+    window_query: &Query<&Window, With<PrimaryWindow>>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+) -> Option<IVec2> {
+    let window = window_query.get_single().ok()?;
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+    cursor_to_world(window, camera, camera_transform).map(world_to_tile)
+}
+
+fn build_editor_panel(commands: &mut Commands, asset_server: &Res<AssetServer>, tools: &EditorTools) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+    let button_style = Style {
+        height: Val::Px(28.0),
+        padding: UiRect::horizontal(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    width: Val::Px(260.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: PANEL_COLOR.into(),
+                ..default()
+            },
+            Interaction::default(),
+            EditorPanel {},
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Editor", get_button_text_style(asset_server)));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for tool in EditorTool::ALL {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: NORMAL_BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                                ToolButton { tool },
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(tool.label(), text_style.clone()));
+                            });
+                    }
+                });
+            for (index, brush) in tools.palette.brushes.iter().enumerate() {
+                let label = match brush {
+                    Brush::Tile(_) => format!("Tile: {}", brush.label()),
+                    Brush::Structure(_) => format!("Structure: {}", brush.label()),
+                };
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                justify_content: JustifyContent::FlexStart,
+                                ..button_style.clone()
+                            },
+                            background_color: NORMAL_BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        PaletteButton { index },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                    });
+            }
+            parent.spawn(TextBundle::from_section(
+                "Left click place, right click erase\n1-3 tools, Ctrl+Z undo, Ctrl+Y redo\nCtrl+S export, Esc leave",
+                TextStyle {
+                    color: Color::rgb(0.75, 0.75, 0.75),
+                    ..text_style.clone()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(tools.status.clone(), text_style.clone()),
+                EditorStatusText {},
+            ));
+        });
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use std::collections::VecDeque;
+
+use crate::building::resources::*;
+use crate::editor::resources::*;
+use crate::world::resources::*;
+use crate::world::tiles::*;
+use crate::world::utils::*;
+
+// Keeps a fill across a whole ocean from stalling the frame
+const MAX_FILL_TILES: usize = 20_000;
+
+// Every tile an edit at this position could change, including all of a structure standing on it
+fn affected_tiles(the_world: &TheWorld, brush: Option<Brush>, tile_pos: IVec2) -> Vec<IVec2> {
+    let mut tiles = vec![tile_pos];
+    if let Some((anchor, structure_type)) = the_world.structure_at(tile_pos) {
+        tiles.extend(footprint_tiles(anchor, structure_type).map(|(covered, _, _)| covered));
+    }
+    if let Some(Brush::Structure(structure_type)) = brush {
+        tiles.extend(footprint_tiles(tile_pos, structure_type).map(|(covered, _, _)| covered));
+    }
+    tiles
+}
+
+// Unlike building in game this paints under structures, clearing the ones that could no longer stand there
+fn paint_tile(the_world: &mut TheWorld, tile_pos: IVec2, tile_type: TileType) {
+    if !tile_type.is_walkable() {
+        the_world.remove_structure(tile_pos);
+    }
+    if let Some(mut tile) = the_world.get_tile(tile_pos).copied() {
+        if tile.tile_type != tile_type {
+            tile.tile_type = tile_type;
+            the_world.set_tile(tile_pos, tile);
+        }
+    }
+}
+
+pub fn place(the_world: &mut TheWorld, history: &mut EditHistory, brush: Brush, tile_pos: IVec2) {
+    let tiles = affected_tiles(the_world, Some(brush), tile_pos);
+    history.record(the_world, &tiles, |the_world| match brush {
+        Brush::Tile(tile_type) => paint_tile(the_world, tile_pos, tile_type),
+        Brush::Structure(structure_type) => {
+            the_world.add_structure(tile_pos, structure_type);
+        }
+    });
+}
+
+pub fn erase(the_world: &mut TheWorld, history: &mut EditHistory, tile_pos: IVec2) {
+    let tiles = affected_tiles(the_world, None, tile_pos);
+    history.record(the_world, &tiles, |the_world| {
+        the_world.remove_structure(tile_pos);
+    });
+}
+
+// Places the brush on, or erases structures from, every tile in turn. Structures
+// go in row by row from the bottom left, so larger ones pack next to each other.
+pub fn edit_area(the_world: &mut TheWorld, history: &mut EditHistory, brush: Option<Brush>, tiles: &[IVec2]) {
+    for tile_pos in tiles {
+        match brush {
+            Some(brush) => place(the_world, history, brush, *tile_pos),
+            None => erase(the_world, history, *tile_pos),
+        }
+    }
+}
+
+pub fn rectangle(from: IVec2, to: IVec2) -> Vec<IVec2> {
+    let (min, max) = (from.min(to), from.max(to));
+    (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+        .collect()
+}
+
+// The connected tiles of the same type as the start, only through generated chunks
+pub fn flood_region(the_world: &TheWorld, start: IVec2) -> Vec<IVec2> {
+    let Some(tile_type) = the_world.get_tile(start).map(|tile| tile.tile_type) else {
+        return vec![];
+    };
+    let mut region: Vec<IVec2> = vec![];
+    let mut seen: HashSet<IVec2> = HashSet::from([start]);
+    let mut queue: VecDeque<IVec2> = VecDeque::from([start]);
+    while let Some(tile_pos) = queue.pop_front() {
+        region.push(tile_pos);
+        if region.len() >= MAX_FILL_TILES {
+            break;
+        }
+        for step in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            let next = tile_pos + step;
+            if the_world.get_tile(next).is_some_and(|tile| tile.tile_type == tile_type) && seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    region.sort_by_key(|tile_pos| (tile_pos.y, tile_pos.x));
+    region
+}
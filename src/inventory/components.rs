@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::items::*;
+
+pub const INVENTORY_SLOTS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: ItemType,
+    pub count: u32,
+}
+
+// A fixed row of slots, each holding one stack of up to the item's max stack size
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Inventory {
+            slots: vec![None; INVENTORY_SLOTS],
+        }
+    }
+}
+
+impl Inventory {
+    pub fn count(&self, item: ItemType) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    // Tops up existing stacks before starting new ones, returns how many did not fit
+    pub fn add(&mut self, item: ItemType, count: u32) -> u32 {
+        let max_stack = item.max_stack();
+        let mut remaining = count;
+        for stack in self.slots.iter_mut().flatten() {
+            if remaining == 0 {
+                break;
+            }
+            if stack.item == item && stack.count < max_stack {
+                let added = remaining.min(max_stack - stack.count);
+                stack.count += added;
+                remaining -= added;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if remaining == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let added = remaining.min(max_stack);
+                *slot = Some(ItemStack { item, count: added });
+                remaining -= added;
+            }
+        }
+        remaining
+    }
+
+    // Whether every one of the items fits at once
+    pub fn can_add(&self, items: &[(ItemType, u32)]) -> bool {
+        let mut inventory = self.clone();
+        items.iter().all(|(item, count)| inventory.add(*item, *count) == 0)
+    }
+
+    // Takes all of them or none, emptying later slots first
+    pub fn remove(&mut self, item: ItemType, count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+        let mut remaining = count;
+        for slot in self.slots.iter_mut().rev() {
+            if remaining == 0 {
+                break;
+            }
+            if let Some(stack) = slot.filter(|stack| stack.item == item) {
+                let removed = remaining.min(stack.count);
+                remaining -= removed;
+                *slot = (stack.count > removed).then_some(ItemStack {
+                    item,
+                    count: stack.count - removed,
+                });
+            }
+        }
+        true
+    }
+}
+
+#[derive(Component)]
+pub struct InventoryHud {}
+
+#[derive(Component)]
+pub struct InventorySlotIcon {
+    pub index: usize,
+}
+
+#[derive(Component)]
+pub struct InventorySlotText {
+    pub index: usize,
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::world::registry::*;
+
+// An id into the item definitions loaded from `assets/items.ron`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemType(u16);
+
+static ITEM_REGISTRY: RwLock<Registry<ItemDefinition>> = RwLock::new(Registry::new());
+
+impl Named for ItemType {
+    fn named(name: &str) -> ItemType {
+        ItemType(intern(&ITEM_REGISTRY, name))
+    }
+
+    fn name(&self) -> &'static str {
+        ITEM_REGISTRY.read().unwrap().name(self.0)
+    }
+}
+
+impl ItemType {
+    pub fn definition(&self) -> ItemDefinition {
+        let registry = ITEM_REGISTRY.read().unwrap();
+        match registry.definition(self.0) {
+            Some(definition) => definition.clone(),
+            None => ItemDefinition::new(*self),
+        }
+    }
+
+    pub fn is_defined(&self) -> bool {
+        ITEM_REGISTRY.read().unwrap().definition(self.0).is_some()
+    }
+
+    pub fn max_stack(&self) -> u32 {
+        let registry = ITEM_REGISTRY.read().unwrap();
+        registry
+            .definition(self.0)
+            .map(|definition| definition.max_stack)
+            .unwrap_or(ItemDefinition::new(*self).max_stack)
+            .max(1)
+    }
+}
+
+impl Serialize for ItemType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_named(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ItemType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_named(deserializer)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ItemDefinition {
+    pub name: ItemType,
+    // Shown in the inventory and crafting menus, falls back to the name
+    pub label: Option<String>,
+    // Relative to the assets folder
+    pub icon: Option<String>,
+    pub max_stack: u32,
+}
+
+impl ItemDefinition {
+    fn new(name: ItemType) -> Self {
+        ItemDefinition {
+            name,
+            label: None,
+            icon: None,
+            max_stack: 99,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| self.name.name().to_string())
+    }
+}
+
+impl Default for ItemDefinition {
+    fn default() -> Self {
+        ItemDefinition::new(ItemType::named("Wood"))
+    }
+}
+
+pub fn item_definitions_path() -> PathBuf {
+    PathBuf::from("assets/items.ron")
+}
+
+// On failure the current definitions are kept
+pub fn load_item_definitions() -> Result<(), String> {
+    let definitions: Vec<ItemDefinition> = load_definitions(&item_definitions_path())?;
+    ITEM_REGISTRY.write().unwrap().set_definitions(
+        definitions
+            .into_iter()
+            .map(|definition| (definition.name.0, definition))
+            .collect(),
+    );
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+pub mod components;
+pub mod items;
+mod systems;
+
+use crate::resources::*;
+use systems::*;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_items)
+            .add_systems(OnEnter(GameState::Game), spawn_inventory_hud)
+            .add_systems(OnEnter(GameState::Menu), despawn_inventory_hud)
+            .add_systems(Update, refresh_inventory_hud.run_if(in_state(GameState::Game)));
+    }
+}
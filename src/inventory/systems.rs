@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::inventory::components::*;
+use crate::inventory::items::*;
+use crate::resources::*;
+
+const SLOT_SIZE: f32 = 52.0;
+const SLOT_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.85);
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.5);
+
+pub fn load_items() {
+    if let Err(err) = load_item_definitions() {
+        error!("Failed to load definitions: {}", err);
+    }
+}
+
+pub fn spawn_inventory_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut reader: EventReader<GameStart>,
+) {
+    if let Some(_game_start) = reader.read().last() {
+        build_inventory_hud(&mut commands, &asset_server);
+    }
+}
+
+pub fn despawn_inventory_hud(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    hud_query: Query<Entity, With<InventoryHud>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        for hud_entity in hud_query.iter() {
+            commands.entity(hud_entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn refresh_inventory_hud(
+    asset_server: Res<AssetServer>,
+    inventory_query: Query<&Inventory, (With<Player>, Changed<Inventory>)>,
+    mut icon_query: Query<(&InventorySlotIcon, &mut UiImage, &mut Visibility)>,
+    mut text_query: Query<(&InventorySlotText, &mut Text)>,
+) {
+    let Ok(inventory) = inventory_query.get_single() else {
+        return;
+    };
+    for (slot_icon, mut image, mut visibility) in icon_query.iter_mut() {
+        let icon = inventory.slots.get(slot_icon.index).copied().flatten().and_then(|stack| stack.item.definition().icon);
+        match icon {
+            Some(icon) => {
+                image.texture = asset_server.load(icon);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (slot_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match inventory.slots.get(slot_text.index).copied().flatten() {
+            // Items without an icon are spelled out instead
+            Some(stack) if stack.item.definition().icon.is_none() => {
+                format!("{} {}", stack.item.definition().label(), stack.count)
+            }
+            Some(stack) if stack.count > 1 => stack.count.to_string(),
+            _ => String::new(),
+        };
+    }
+}
+
+fn build_inventory_hud(commands: &mut Commands, asset_server: &AssetServer) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            InventoryHud {},
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(6.0),
+                        padding: UiRect::all(Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for index in 0..INVENTORY_SLOTS {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(SLOT_SIZE),
+                                    height: Val::Px(SLOT_SIZE),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: SLOT_COLOR.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    ImageBundle {
+                                        style: Style {
+                                            width: Val::Px(SLOT_SIZE - 12.0),
+                                            height: Val::Px(SLOT_SIZE - 12.0),
+                                            ..default()
+                                        },
+                                        visibility: Visibility::Hidden,
+                                        ..default()
+                                    },
+                                    InventorySlotIcon { index },
+                                ));
+                                parent.spawn((
+                                    TextBundle {
+                                        text: Text::from_section("", text_style.clone()),
+                                        style: Style {
+                                            position_type: PositionType::Absolute,
+                                            right: Val::Px(3.0),
+                                            bottom: Val::Px(1.0),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    },
+                                    InventorySlotText { index },
+                                ));
+                            });
+                    }
+                });
+        });
+}
@@ -1,30 +1,57 @@
-use bevy::{
-    prelude::*,
-    window::{PresentMode, WindowMode},
-};
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::prelude::*;
+use std::path::PathBuf;
+use building::BuildingPlugin;
+use controls::ControlsPlugin;
+use crafting::CraftingPlugin;
+use daylight::DaylightPlugin;
+use editor::EditorPlugin;
+use inventory::InventoryPlugin;
+use npc::NpcPlugin;
+use pathfinding::PathfindingPlugin;
+use weather::WeatherPlugin;
 
 mod menu;
 mod player;
 mod resources;
+mod save;
+mod settings;
 mod systems;
 mod world;
 mod components;
+mod controls;
+mod inventory;
+mod crafting;
+mod building;
+mod editor;
+mod daylight;
+mod weather;
+mod npc;
+mod pathfinding;
 
 use menu::MainMenuPlugin;
 use player::PlayerPlugin;
 use resources::*;
+use save::SavePlugin;
+use settings::{utils::load_settings, SettingsPlugin};
 use systems::*;
-use world::WorldPlugin;
+use world::{export::*, WorldPlugin};
 
 fn main() {
+    if generate_from_args() {
+        return;
+    }
+    let settings = load_settings();
+
     App::new()
         .add_plugins((
             DefaultPlugins.set(WindowPlugin {
                 primary_window: Some(Window {
                     title: "Shoyu".into(),
-                    resolution: (1920., 1080.).into(),
-                    mode: WindowMode::Fullscreen,
-                    present_mode: PresentMode::AutoVsync,
+                    resolution: settings.window_resolution(),
+                    mode: settings.window_mode.window_mode(),
+                    present_mode: settings.present_mode(),
                     ..default()
                 }),
                 ..default()
@@ -32,9 +59,87 @@ fn main() {
             MainMenuPlugin,
             WorldPlugin,
             PlayerPlugin,
-        )).init_state::<GameState>()
+            SavePlugin,
+            SettingsPlugin,
+            ControlsPlugin,
+            InventoryPlugin,
+            CraftingPlugin,
+            BuildingPlugin,
+            EditorPlugin,
+            DaylightPlugin,
+            WeatherPlugin,
+            NpcPlugin,
+            PathfindingPlugin,
+        ))
+        .insert_resource(settings)
+        .init_state::<GameState>()
         .add_event::<GameStart>()
         .add_event::<GameOver>()
+        .add_event::<TilesChanged>()
+        .add_event::<SaveGame>()
+        .add_event::<LoadGame>()
+        .insert_resource(seed_from_args().unwrap_or_default())
+        .insert_resource(generator_from_args())
+        .insert_resource(map_from_args())
         .add_systems(Startup, spawn_camera)
         .run();
 }
+
+// Headless `--generate` mode, runs instead of the game and never opens a window
+fn generate_from_args() -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    let result = ExportOptions::from_args(&args).and_then(|options| match options {
+        Some(options) => export_world(&options).map(Some),
+        None => Ok(None),
+    });
+    match result {
+        Ok(Some(paths)) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+            true
+        }
+        Ok(None) => false,
+        Err(err) => {
+            eprintln!("World generation failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn generator_from_args() -> WorldGenerator {
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|arg| arg == "--generator") {
+        Some(index) => match generator_from_arg(args.get(index + 1)) {
+            Ok(kind) => WorldGenerator { kind },
+            Err(err) => {
+                eprintln!("{}", err);
+                WorldGenerator::default()
+            }
+        },
+        None => WorldGenerator::default(),
+    }
+}
+
+fn map_from_args() -> WorldMap {
+    let args: Vec<String> = std::env::args().collect();
+    let path = args
+        .iter()
+        .position(|arg| arg == "--map")
+        .and_then(|index| args.get(index + 1))
+        .map(PathBuf::from);
+    WorldMap {
+        selected: path.is_some(),
+        path,
+    }
+}
+
+fn seed_from_args() -> Option<WorldSeed> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            return args.next().and_then(|seed| seed.parse().ok()).map(|seed| WorldSeed { seed });
+        }
+    }
+    None
+}
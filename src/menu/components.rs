@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 
+use crate::controls::resources::*;
 
 #[derive(Component)]
 pub struct MainMenu {}
@@ -14,12 +15,71 @@ pub struct QuitButton {}
 pub struct OptionsButton {}
 
 #[derive(Component)]
-pub struct GameCursor {
-    pub despawned: bool
+pub struct ContinueButton {}
+
+#[derive(Component)]
+pub struct SaveSlotButton {
+    pub slot: usize,
+}
+
+#[derive(Component)]
+pub struct SeedText {}
+
+#[derive(Component, Clone, Copy)]
+pub enum WorldButton {
+    NewSeed,
+    Generator,
+    Map,
+    Editor,
+}
+
+#[derive(Component)]
+pub struct OptionsMenu {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionSetting {
+    Resolution,
+    WindowMode,
+    Vsync,
+    ShowFps,
+    ShowCoordinates,
+    MinZoom,
+    MaxZoom,
+    GamepadDeadzone,
+    ClickToMove,
+}
+
+#[derive(Component, Clone, Copy)]
+pub enum OptionButton {
+    Previous(OptionSetting),
+    Next(OptionSetting),
+    Controls,
+    Back,
 }
 
 #[derive(Component)]
-pub struct FPS {}
+pub struct OptionValueText {
+    pub setting: OptionSetting,
+}
+
+#[derive(Component)]
+pub struct ControlsMenu {}
+
+#[derive(Component, Clone, Copy)]
+pub enum ControlsButton {
+    Binding { action: Action, slot: usize },
+    ResetDefaults,
+    Back,
+}
+
+#[derive(Component)]
+pub struct BindingConflictText {}
+
+#[derive(Component)]
+pub struct GameCursor {}
+
+#[derive(Component)]
+pub struct Fps {}
 
 #[derive(Component)]
 pub struct Cords {}
@@ -1,9 +1,10 @@
 mod components;
 mod resources;
-mod styles;
+pub mod styles;
 mod systems;
 
 use crate::resources::*;
+use crate::settings::resources::*;
 use bevy::prelude::*;
 use resources::*;
 use systems::*;
@@ -15,22 +16,59 @@ impl Plugin for MainMenuPlugin {
         app.init_resource::<FixMenuTimer>()
             .init_resource::<FpsTracker>()
             .init_resource::<DrawCordsTracker>()
-            .add_systems(Startup, setup_cursor)
+            .init_resource::<PendingRebind>()
+            .init_resource::<MenuFocus>()
+            .init_state::<OptionsMenuState>()
+            .add_systems(Startup, (setup_cursor, apply_overlay_settings))
             .add_systems(OnEnter(GameState::Menu), spawn_main_menu)
             .add_systems(OnEnter(GameState::Paused), spawn_main_menu)
+            .add_systems(OnEnter(OptionsMenuState::Open), spawn_options_menu)
+            .add_systems(OnExit(OptionsMenuState::Open), despawn_options_menu)
+            .add_systems(OnEnter(OptionsMenuState::Controls), spawn_controls_menu)
+            .add_systems(OnExit(OptionsMenuState::Controls), despawn_controls_menu)
             .add_systems(
                 Update,
                 (
                     fix_menu_first_game.run_if(in_state(GameState::Menu)),
-                    interact_play_button,
-                    interact_quit_button,
-                    interact_options_button,
-                    despawn_main_menu.run_if(in_state(GameState::Game)),
-                    pause_game.run_if(not(in_state(GameState::Menu))),
+                    (edit_world_seed, refresh_seed_text)
+                        .chain()
+                        .run_if(in_state(GameState::Menu).and_then(in_state(OptionsMenuState::Closed))),
+                    (navigate_menu_focus, highlight_focused_button)
+                        .chain()
+                        .in_set(MenuNavigation),
+                    (
+                        interact_play_button,
+                        interact_quit_button,
+                        interact_options_button,
+                        interact_continue_button,
+                        interact_save_slot_buttons,
+                        interact_world_buttons,
+                    )
+                        .after(MenuNavigation),
+                    despawn_main_menu.run_if(in_state(GameState::Game).or_else(in_state(GameState::Editor))),
+                    pause_game.run_if(
+                        not(in_state(GameState::Menu))
+                            .and_then(in_state(OptionsMenuState::Closed))
+                            .and_then(in_state(CraftingMenuState::Closed)),
+                    ),
+                    close_options_menu.run_if(not(in_state(OptionsMenuState::Closed))),
+                    interact_option_buttons
+                        .after(MenuNavigation)
+                        .run_if(in_state(OptionsMenuState::Open)),
+                    refresh_option_values.run_if(resource_changed::<Settings>),
+                    (
+                        interact_controls_buttons,
+                        capture_rebind.after(close_options_menu),
+                        refresh_binding_texts,
+                    )
+                        .chain()
+                        .after(MenuNavigation)
+                        .run_if(in_state(OptionsMenuState::Controls)),
                     move_cursor,
                     fps_system,
                     draw_cords.run_if(in_state(GameState::Game)),
-                    zoom_camera_system.run_if(in_state(GameState::Game))
+                    zoom_camera_system.run_if(in_state(GameState::Game).or_else(in_state(GameState::Editor))),
+                    clamp_camera_zoom.run_if(resource_changed::<Settings>),
                 ),
             );
     }
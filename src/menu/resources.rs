@@ -1,5 +1,26 @@
 use bevy::prelude::*;
 
+use crate::controls::resources::*;
+
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum OptionsMenuState {
+    #[default]
+    Closed,
+    Open,
+    Controls,
+}
+
+#[derive(Resource, Default)]
+pub struct MenuFocus {
+    pub focused: Option<Entity>,
+    pub pressed: Option<Entity>,
+}
+
+#[derive(Resource, Default)]
+pub struct PendingRebind {
+    pub target: Option<(Action, usize)>,
+}
+
 #[derive(Resource)]
 pub struct FixMenuTimer {
     pub timer: Timer,
@@ -38,6 +38,139 @@ pub fn button_style() -> Style {
     }
 }
 
+pub fn options_menu_style(window_query: Query<&Window, With<PrimaryWindow>>) -> Style {
+    let window = window_query.get_single().unwrap();
+
+    Style {
+        flex_direction: FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        width: Val::Px(window.width()),
+        height: Val::Px(window.height()),
+        ..default()
+    }
+}
+
+pub fn option_row_style() -> Style {
+    Style {
+        flex_direction: FlexDirection::Row,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        margin: UiRect::vertical(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+pub fn option_label_style() -> Style {
+    Style {
+        width: Val::Px(260.0),
+        ..default()
+    }
+}
+
+pub fn option_arrow_button_style() -> Style {
+    Style {
+        width: Val::Px(50.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        border: UiRect::all(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+pub fn option_value_style() -> Style {
+    Style {
+        width: Val::Px(240.0),
+        justify_content: JustifyContent::Center,
+        ..default()
+    }
+}
+
+pub fn option_wide_button_style() -> Style {
+    Style {
+        width: Val::Px(220.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        border: UiRect::all(Val::Px(5.0)),
+        margin: UiRect::horizontal(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+// Two columns of bindings, so every action fits on the smallest resolution
+pub fn binding_grid_style() -> Style {
+    Style {
+        flex_direction: FlexDirection::Row,
+        flex_wrap: FlexWrap::Wrap,
+        justify_content: JustifyContent::Center,
+        width: Val::Px(1200.0),
+        ..default()
+    }
+}
+
+pub fn binding_row_style() -> Style {
+    Style {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        width: Val::Px(600.0),
+        margin: UiRect::vertical(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+pub fn binding_label_style() -> Style {
+    Style {
+        width: Val::Px(210.0),
+        ..default()
+    }
+}
+
+pub fn binding_button_style() -> Style {
+    Style {
+        width: Val::Px(180.0),
+        height: Val::Px(40.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        margin: UiRect::horizontal(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+pub fn save_slot_row_style() -> Style {
+    Style {
+        flex_direction: FlexDirection::Row,
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        margin: UiRect::top(Val::Px(10.0)),
+        ..default()
+    }
+}
+
+pub fn save_slot_button_style() -> Style {
+    Style {
+        width: Val::Px(120.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        border: UiRect::all(Val::Px(5.0)),
+        margin: UiRect::horizontal(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+pub fn world_button_style() -> Style {
+    Style {
+        width: Val::Px(180.0),
+        height: Val::Px(50.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        margin: UiRect::horizontal(Val::Px(5.0)),
+        ..default()
+    }
+}
+
 pub fn title_style() -> Style {
     Style {
         flex_direction: FlexDirection::Row,
@@ -61,6 +194,22 @@ pub fn get_title_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
     }
 }
 
+pub fn get_heading_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+        font_size: 96.0,
+        color: Color::WHITE,
+    }
+}
+
+pub fn get_button_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/Righteous-Regular.ttf"),
+        font_size: 30.0,
+        color: Color::WHITE,
+    }
+}
+
 pub fn get_shadow_text_style(asset_server: &Res<AssetServer>) -> TextStyle {
     TextStyle {
         font: asset_server.load("fonts/Righteous-Regular.ttf"),
@@ -1,16 +1,21 @@
 use bevy::{
     app::AppExit,
-    input::mouse::MouseWheel,
+    input::{keyboard::KeyboardInput, mouse::MouseWheel, ButtonState},
     prelude::*,
     render::camera::OrthographicProjection,
     window::{CursorGrabMode, PrimaryWindow},
 };
 
 use crate::components::*;
+use crate::controls::resources::*;
+use crate::controls::utils::*;
 use crate::menu::components::*;
 use crate::menu::resources::*;
 use crate::menu::styles::*;
 use crate::resources::*;
+use crate::save::resources::*;
+use crate::save::utils::*;
+use crate::settings::resources::*;
 
 pub fn setup_cursor(
     mut windows: Query<&mut Window>,
@@ -32,7 +37,7 @@ pub fn setup_cursor(
             transform: Transform::from_translation(cursor_spawn),
             ..default()
         },
-        GameCursor { despawned: false },
+        GameCursor {},
     ));
 }
 
@@ -40,11 +45,12 @@ pub fn move_cursor(
     mut windows: Query<&mut Window>,
     mut cursor: Query<(Entity, &mut Style), With<GameCursor>>,
     game_state_const: Res<State<GameState>>,
+    crafting_menu_state: Res<State<CraftingMenuState>>,
 ) {
     let mut window: Mut<Window> = windows.single_mut();
     match *game_state_const.get() {
-        GameState::Game => {
-            window.cursor.grab_mode = CursorGrabMode::Locked;
+        GameState::Game if *crafting_menu_state.get() == CraftingMenuState::Closed => {
+            window.cursor.grab_mode = CursorGrabMode::Confined;
         }
         _ => {
             window.cursor.grab_mode = CursorGrabMode::None;
@@ -68,22 +74,33 @@ pub fn fix_menu_first_game(
     mut timer: ResMut<FixMenuTimer>,
     time: Res<Time>,
     game_state_const: Res<State<GameState>>,
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
 ) {
     if let Ok(menu_entity) = main_menu_query.get_single() {
         timer.timer.tick(time.delta());
         if timer.timer.just_finished() {
             commands.entity(menu_entity).despawn_recursive();
-            build_main_menu(&mut commands, &asset_server, window_query, game_state_const);
+            build_main_menu(
+                &mut commands,
+                &asset_server,
+                window_query,
+                game_state_const,
+                &world_seed,
+                &world_generator,
+                &world_map,
+            );
         }
     }
 }
 
 pub fn pause_game(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
     mut game_state: ResMut<NextState<GameState>>,
     game_state_const: Res<State<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::Pause) {
         match *game_state_const.get() {
             GameState::Game => {
                 game_state.set(GameState::Paused);
@@ -91,7 +108,97 @@ pub fn pause_game(
             GameState::Paused => {
                 game_state.set(GameState::Game);
             }
-            GameState::Menu => {}
+            GameState::Menu | GameState::Editor => {}
+        }
+    }
+}
+
+pub fn navigate_menu_focus(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut menu_focus: ResMut<MenuFocus>,
+    mut focusable_query: Query<(Entity, &Focusable, &mut Interaction)>,
+) {
+    // Release the button activated last frame so it can be activated again
+    if let Some(pressed) = menu_focus.pressed.take() {
+        if let Ok((_, _, mut interaction)) = focusable_query.get_mut(pressed) {
+            if *interaction == Interaction::Pressed {
+                *interaction = Interaction::None;
+            }
+        }
+    }
+
+    let mut focusables: Vec<(Entity, usize)> = focusable_query
+        .iter()
+        .map(|(entity, focusable, _)| (entity, focusable.order))
+        .collect();
+    focusables.sort_by_key(|(_, order)| *order);
+    let current = menu_focus
+        .focused
+        .and_then(|focused| focusables.iter().position(|(entity, _)| *entity == focused));
+
+    let mut step: i32 = 0;
+    let mut activate = false;
+    for button in gamepad_input.get_just_pressed() {
+        match button.button_type {
+            GamepadButtonType::DPadUp => step -= 1,
+            GamepadButtonType::DPadDown => step += 1,
+            GamepadButtonType::South => activate = true,
+            _ => {}
+        }
+    }
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    for key in keyboard_input.get_just_pressed() {
+        match key {
+            KeyCode::ArrowUp => step -= 1,
+            KeyCode::ArrowDown => step += 1,
+            KeyCode::Tab if shift => step -= 1,
+            KeyCode::Tab => step += 1,
+            KeyCode::Enter | KeyCode::NumpadEnter => activate = true,
+            _ => {}
+        }
+    }
+
+    menu_focus.focused = if focusables.is_empty() {
+        None
+    } else if step != 0 {
+        let len = focusables.len() as i32;
+        let index = match current {
+            Some(index) => (index as i32 + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        };
+        Some(focusables[index as usize].0)
+    } else {
+        current.map(|index| focusables[index].0)
+    };
+
+    // Pressing the button through its Interaction runs the exact same handler as a mouse click
+    if activate {
+        if let Some(focused) = menu_focus.focused {
+            if let Ok((_, _, mut interaction)) = focusable_query.get_mut(focused) {
+                *interaction = Interaction::Pressed;
+                menu_focus.pressed = Some(focused);
+            }
+        }
+    }
+}
+
+pub fn highlight_focused_button(
+    menu_focus: Res<MenuFocus>,
+    mut button_query: Query<(Entity, &Interaction, &mut BackgroundColor), With<Focusable>>,
+) {
+    for (entity, interaction, mut background_color) in button_query.iter_mut() {
+        if *interaction != Interaction::None {
+            continue;
+        }
+        let color = if menu_focus.focused == Some(entity) {
+            HOVERED_BUTTON_COLOR
+        } else {
+            NORMAL_BUTTON_COLOR
+        };
+        if background_color.0 != color {
+            background_color.0 = color;
         }
     }
 }
@@ -118,7 +225,7 @@ pub fn interact_play_button(
                     GameState::Paused => {
                         game_state.set(GameState::Game);
                     }
-                    GameState::Game => {}
+                    GameState::Game | GameState::Editor => {}
                 }
             }
             Interaction::Hovered => {
@@ -160,7 +267,7 @@ pub fn interact_quit_button(
                         game_state.set(GameState::Menu);
                         game_over_event_writer.send(GameOver {});
                     }
-                    GameState::Game => {}
+                    GameState::Game | GameState::Editor => {}
                 }
             }
             Interaction::Hovered => {
@@ -178,11 +285,13 @@ pub fn interact_options_button(
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<OptionsButton>),
     >,
+    mut options_menu_state: ResMut<NextState<OptionsMenuState>>,
 ) {
     if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *background_color = PRESSED_BUTTON_COLOR.into();
+                options_menu_state.set(OptionsMenuState::Open);
             }
             Interaction::Hovered => {
                 *background_color = HOVERED_BUTTON_COLOR.into();
@@ -194,6 +303,82 @@ pub fn interact_options_button(
     }
 }
 
+pub fn interact_continue_button(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ContinueButton>),
+    >,
+    mut load_game_event_writer: EventWriter<LoadGame>,
+) {
+    if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+
+                if let Some(slot) = latest_slot() {
+                    load_game_event_writer.send(LoadGame { slot });
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+pub fn interact_save_slot_buttons(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor, &SaveSlotButton, &Children),
+        Changed<Interaction>,
+    >,
+    mut text_query: Query<&mut Text>,
+    game_state_const: Res<State<GameState>>,
+    mut save_game_event_writer: EventWriter<SaveGame>,
+    mut load_game_event_writer: EventWriter<LoadGame>,
+) {
+    for (interaction, mut background_color, save_slot_button, children) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+                let slot = save_slot_button.slot;
+
+                match *game_state_const.get() {
+                    GameState::Menu => {
+                        if slot_exists(slot) {
+                            load_game_event_writer.send(LoadGame { slot });
+                        }
+                    }
+                    GameState::Paused => {
+                        save_game_event_writer.send(SaveGame { slot });
+                        for &child in children.iter() {
+                            if let Ok(mut text) = text_query.get_mut(child) {
+                                text.sections[0].value = "Saved".to_string();
+                            }
+                        }
+                    }
+                    GameState::Game | GameState::Editor => {}
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+fn save_slot_label(slot: usize, game_state_const: &Res<State<GameState>>) -> String {
+    match *game_state_const.get() {
+        GameState::Menu if !slot_exists(slot) => "Empty".to_string(),
+        _ => format!("Slot {}", slot + 1),
+    }
+}
+
 pub fn despawn_main_menu(mut commands: Commands, main_menu_query: Query<Entity, With<MainMenu>>) {
     if let Ok(main_menu_entity) = main_menu_query.get_single() {
         commands.entity(main_menu_entity).despawn_recursive();
@@ -205,43 +390,678 @@ pub fn spawn_main_menu(
     asset_server: Res<AssetServer>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     game_state_const: Res<State<GameState>>,
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
 ) {
-    build_main_menu(&mut commands, &asset_server, window_query, game_state_const);
+    build_main_menu(
+        &mut commands,
+        &asset_server,
+        window_query,
+        game_state_const,
+        &world_seed,
+        &world_generator,
+        &world_map,
+    );
 }
 
-fn play_or_resume(game_state_const: &Res<State<GameState>>) -> &'static str {
-    match *game_state_const.get() {
-        GameState::Menu => {
-            return "sprites/Play-Button.png";
+pub fn apply_overlay_settings(
+    settings: Res<Settings>,
+    mut fps_tracker: ResMut<FpsTracker>,
+    mut draw_cords_tracker: ResMut<DrawCordsTracker>,
+) {
+    fps_tracker.enabled = settings.show_fps;
+    draw_cords_tracker.enabled = settings.show_coordinates;
+}
+
+pub fn spawn_options_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    main_menu_query: Query<Entity, With<MainMenu>>,
+    settings: Res<Settings>,
+) {
+    if let Ok(main_menu_entity) = main_menu_query.get_single() {
+        commands.entity(main_menu_entity).despawn_recursive();
+    }
+    build_options_menu(&mut commands, &asset_server, window_query, &settings);
+}
+
+pub fn despawn_options_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    options_menu_query: Query<Entity, With<OptionsMenu>>,
+    game_state_const: Res<State<GameState>>,
+    options_menu_state_const: Res<State<OptionsMenuState>>,
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
+) {
+    if let Ok(options_menu_entity) = options_menu_query.get_single() {
+        commands.entity(options_menu_entity).despawn_recursive();
+    }
+    // Moving on to the controls screen rather than closing the options
+    if *options_menu_state_const.get() != OptionsMenuState::Closed {
+        return;
+    }
+    if *game_state_const.get() != GameState::Game {
+        build_main_menu(
+            &mut commands,
+            &asset_server,
+            window_query,
+            game_state_const,
+            &world_seed,
+            &world_generator,
+            &world_map,
+        );
+    }
+}
+
+pub fn close_options_menu(
+    actions: Res<ButtonInput<Action>>,
+    pending_rebind: Res<PendingRebind>,
+    mut options_menu_state: ResMut<NextState<OptionsMenuState>>,
+    options_menu_state_const: Res<State<OptionsMenuState>>,
+) {
+    if !actions.just_pressed(Action::Pause) || pending_rebind.target.is_some() {
+        return;
+    }
+    match *options_menu_state_const.get() {
+        OptionsMenuState::Controls => options_menu_state.set(OptionsMenuState::Open),
+        OptionsMenuState::Open => options_menu_state.set(OptionsMenuState::Closed),
+        OptionsMenuState::Closed => {}
+    }
+}
+
+pub fn spawn_controls_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+) {
+    build_controls_menu(&mut commands, &asset_server, window_query, &settings);
+}
+
+pub fn despawn_controls_menu(
+    mut commands: Commands,
+    controls_menu_query: Query<Entity, With<ControlsMenu>>,
+    mut pending_rebind: ResMut<PendingRebind>,
+) {
+    if let Ok(controls_menu_entity) = controls_menu_query.get_single() {
+        commands.entity(controls_menu_entity).despawn_recursive();
+    }
+    pending_rebind.target = None;
+}
+
+pub fn interact_controls_buttons(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor, &ControlsButton),
+        Changed<Interaction>,
+    >,
+    mut settings: ResMut<Settings>,
+    mut pending_rebind: ResMut<PendingRebind>,
+    mut options_menu_state: ResMut<NextState<OptionsMenuState>>,
+) {
+    for (interaction, mut background_color, controls_button) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+
+                match *controls_button {
+                    ControlsButton::Binding { action, slot } => {
+                        pending_rebind.target = Some((action, slot));
+                    }
+                    ControlsButton::ResetDefaults => {
+                        settings.key_bindings = KeyBindings::default();
+                        pending_rebind.target = None;
+                    }
+                    ControlsButton::Back => options_menu_state.set(OptionsMenuState::Open),
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
         }
-        GameState::Paused => {
-            return "sprites/Resume-Button.png";
+    }
+}
+
+// Escape cancels a rebind and Delete clears the slot, anything else becomes the new key
+pub fn capture_rebind(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut pending_rebind: ResMut<PendingRebind>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some((action, slot)) = pending_rebind.target else {
+        return;
+    };
+    if let Some(&key) = keyboard_input.get_just_pressed().next() {
+        match key {
+            KeyCode::Escape => {}
+            KeyCode::Delete => settings.key_bindings.bind(action, slot, None),
+            _ => settings.key_bindings.bind(action, slot, Some(key)),
         }
-        GameState::Game => {
-            return "sprites/Play-Button.png";
+        pending_rebind.target = None;
+    }
+}
+
+pub fn refresh_binding_texts(
+    settings: Res<Settings>,
+    pending_rebind: Res<PendingRebind>,
+    button_query: Query<(&ControlsButton, &Children)>,
+    mut text_query: Query<&mut Text, Without<BindingConflictText>>,
+    mut conflict_text_query: Query<&mut Text, With<BindingConflictText>>,
+) {
+    for (controls_button, children) in button_query.iter() {
+        let ControlsButton::Binding { action, slot } = *controls_button else {
+            continue;
+        };
+        let key = settings.key_bindings.keys(action)[slot];
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                if pending_rebind.target == Some((action, slot)) {
+                    text.sections[0].value = "Press a key".to_string();
+                    text.sections[0].style.color = Color::WHITE;
+                } else {
+                    text.sections[0].value = key_label(key);
+                    text.sections[0].style.color = match key {
+                        Some(key) if settings.key_bindings.is_conflicting(action, key) => {
+                            Color::rgb(0.9, 0.3, 0.3)
+                        }
+                        _ => Color::WHITE,
+                    };
+                }
+            }
         }
     }
+
+    if let Ok(mut text) = conflict_text_query.get_single_mut() {
+        text.sections[0].value = match settings.key_bindings.conflicts().first() {
+            Some((action, other, key)) => format!(
+                "{} is used by both {} and {}",
+                key_label(Some(*key)),
+                action.label(),
+                other.label()
+            ),
+            None => String::new(),
+        };
+    }
 }
 
-fn quit_or_main_menu(game_state_const: &Res<State<GameState>>) -> &'static str {
-    match *game_state_const.get() {
-        GameState::Menu => {
-            return "sprites/Quit-Button.png";
+fn build_controls_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    settings: &Settings,
+) -> Entity {
+    commands
+        .spawn((
+            NodeBundle {
+                style: options_menu_style(window_query),
+                ..default()
+            },
+            ControlsMenu {},
+        ))
+        .with_children(|parent| {
+            // === Title ===
+            parent.spawn(TextBundle {
+                text: Text::from_section("Controls", get_heading_text_style(asset_server)),
+                style: Style {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+                ..default()
+            });
+            // === Bindings ===
+            parent
+                .spawn(NodeBundle {
+                    style: binding_grid_style(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for action in Action::ALL {
+                        parent
+                            .spawn(NodeBundle {
+                                style: binding_row_style(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle {
+                                    text: Text::from_section(action.label(), get_button_text_style(asset_server)),
+                                    style: binding_label_style(),
+                                    ..default()
+                                });
+                                for slot in 0..BINDING_SLOTS {
+                                    parent
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: binding_button_style(),
+                                                background_color: NORMAL_BUTTON_COLOR.into(),
+                                                ..default()
+                                            },
+                                            ControlsButton::Binding { action, slot },
+                                        ))
+                                        .with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(
+                                                key_label(settings.key_bindings.keys(action)[slot]),
+                                                get_button_text_style(asset_server),
+                                            ));
+                                        });
+                                }
+                            });
+                    }
+                });
+            // === Conflicts ===
+            parent.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            color: Color::rgb(0.9, 0.3, 0.3),
+                            ..get_button_text_style(asset_server)
+                        },
+                    ),
+                    style: Style {
+                        margin: UiRect::vertical(Val::Px(10.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                BindingConflictText {},
+            ));
+            // === Reset / Back Buttons ===
+            parent
+                .spawn(NodeBundle {
+                    style: option_row_style(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, controls_button) in [
+                        ("Reset", ControlsButton::ResetDefaults),
+                        ("Back", ControlsButton::Back),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: option_wide_button_style(),
+                                    background_color: NORMAL_BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                                controls_button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    get_button_text_style(asset_server),
+                                ));
+                            });
+                    }
+                });
+        })
+        .id()
+}
+
+pub fn interact_option_buttons(
+    mut button_query: Query<
+        (&Interaction, &mut BackgroundColor, &OptionButton),
+        Changed<Interaction>,
+    >,
+    mut settings: ResMut<Settings>,
+    mut fps_tracker: ResMut<FpsTracker>,
+    mut draw_cords_tracker: ResMut<DrawCordsTracker>,
+    mut options_menu_state: ResMut<NextState<OptionsMenuState>>,
+) {
+    for (interaction, mut background_color, option_button) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+
+                match *option_button {
+                    OptionButton::Previous(setting) => change_setting(&mut settings, setting, false),
+                    OptionButton::Next(setting) => change_setting(&mut settings, setting, true),
+                    OptionButton::Controls => options_menu_state.set(OptionsMenuState::Controls),
+                    OptionButton::Back => options_menu_state.set(OptionsMenuState::Closed),
+                }
+                fps_tracker.enabled = settings.show_fps;
+                draw_cords_tracker.enabled = settings.show_coordinates;
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+pub fn refresh_option_values(
+    settings: Res<Settings>,
+    mut text_query: Query<(&mut Text, &OptionValueText)>,
+) {
+    for (mut text, option_value_text) in text_query.iter_mut() {
+        text.sections[0].value = option_value_label(&settings, option_value_text.setting);
+    }
+}
+
+fn change_setting(settings: &mut Settings, setting: OptionSetting, forward: bool) {
+    const MAX_ZOOM_STEP: f32 = 0.5;
+    const DEADZONE_STEP: f32 = 0.05;
+    const MAX_DEADZONE: f32 = 0.9;
+
+    let direction = if forward { 1.0 } else { -1.0 };
+    match setting {
+        OptionSetting::Resolution => settings.resolution = settings.cycle_resolution(forward),
+        OptionSetting::WindowMode => settings.window_mode = settings.window_mode.cycle(forward),
+        OptionSetting::Vsync => settings.vsync = !settings.vsync,
+        OptionSetting::ShowFps => settings.show_fps = !settings.show_fps,
+        OptionSetting::ShowCoordinates => settings.show_coordinates = !settings.show_coordinates,
+        OptionSetting::MinZoom => {
+            let min_zoom = settings.min_zoom + MIN_ZOOM_STEP * direction;
+            settings.min_zoom = round_hundredths(min_zoom.clamp(MIN_ZOOM_STEP, settings.max_zoom));
+        }
+        OptionSetting::MaxZoom => {
+            let max_zoom = settings.max_zoom + MAX_ZOOM_STEP * direction;
+            settings.max_zoom = round_hundredths(max_zoom.clamp(settings.min_zoom, ZOOM_LIMIT));
+        }
+        OptionSetting::GamepadDeadzone => {
+            let deadzone = settings.gamepad_deadzone + DEADZONE_STEP * direction;
+            settings.gamepad_deadzone = round_hundredths(deadzone.clamp(0.0, MAX_DEADZONE));
+        }
+        OptionSetting::ClickToMove => settings.click_to_move = !settings.click_to_move,
+    }
+}
+
+fn round_hundredths(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+fn option_label(setting: OptionSetting) -> &'static str {
+    match setting {
+        OptionSetting::Resolution => "Resolution",
+        OptionSetting::WindowMode => "Window Mode",
+        OptionSetting::Vsync => "VSync",
+        OptionSetting::ShowFps => "Show FPS",
+        OptionSetting::ShowCoordinates => "Show Coordinates",
+        OptionSetting::MinZoom => "Min Zoom",
+        OptionSetting::MaxZoom => "Max Zoom",
+        OptionSetting::GamepadDeadzone => "Stick Deadzone",
+        OptionSetting::ClickToMove => "Click to Move",
+    }
+}
+
+fn option_value_label(settings: &Settings, setting: OptionSetting) -> String {
+    let on_off = |enabled: bool| if enabled { "On" } else { "Off" }.to_string();
+    match setting {
+        OptionSetting::Resolution => format!("{}x{}", settings.resolution.0, settings.resolution.1),
+        OptionSetting::WindowMode => format!("{:?}", settings.window_mode),
+        OptionSetting::Vsync => on_off(settings.vsync),
+        OptionSetting::ShowFps => on_off(settings.show_fps),
+        OptionSetting::ShowCoordinates => on_off(settings.show_coordinates),
+        OptionSetting::MinZoom => format!("{:.2}", settings.min_zoom),
+        OptionSetting::MaxZoom => format!("{:.2}", settings.max_zoom),
+        OptionSetting::GamepadDeadzone => format!("{:.2}", settings.gamepad_deadzone),
+        OptionSetting::ClickToMove => on_off(settings.click_to_move),
+    }
+}
+
+fn build_options_menu(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    settings: &Settings,
+) -> Entity {
+    const OPTION_SETTINGS: [OptionSetting; 9] = [
+        OptionSetting::Resolution,
+        OptionSetting::WindowMode,
+        OptionSetting::Vsync,
+        OptionSetting::ShowFps,
+        OptionSetting::ShowCoordinates,
+        OptionSetting::MinZoom,
+        OptionSetting::MaxZoom,
+        OptionSetting::GamepadDeadzone,
+        OptionSetting::ClickToMove,
+    ];
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: options_menu_style(window_query),
+                ..default()
+            },
+            OptionsMenu {},
+        ))
+        .with_children(|parent| {
+            // === Title ===
+            parent.spawn(TextBundle {
+                text: Text::from_section("Options", get_heading_text_style(asset_server)),
+                style: Style {
+                    margin: UiRect::bottom(Val::Px(30.0)),
+                    ..default()
+                },
+                ..default()
+            });
+            // === Settings ===
+            for setting in OPTION_SETTINGS {
+                parent
+                    .spawn(NodeBundle {
+                        style: option_row_style(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(
+                                option_label(setting),
+                                get_button_text_style(asset_server),
+                            ),
+                            style: option_label_style(),
+                            ..default()
+                        });
+                        spawn_option_arrow(parent, asset_server, "<", OptionButton::Previous(setting));
+                        parent
+                            .spawn(NodeBundle {
+                                style: option_value_style(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((
+                                    TextBundle::from_section(
+                                        option_value_label(settings, setting),
+                                        get_button_text_style(asset_server),
+                                    ),
+                                    OptionValueText { setting },
+                                ));
+                            });
+                        spawn_option_arrow(parent, asset_server, ">", OptionButton::Next(setting));
+                    });
+            }
+            // === Controls / Back Buttons ===
+            parent
+                .spawn(NodeBundle {
+                    style: option_row_style(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (label, option_button) in
+                        [("Controls", OptionButton::Controls), ("Back", OptionButton::Back)]
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: option_wide_button_style(),
+                                    background_color: NORMAL_BUTTON_COLOR.into(),
+                                    ..default()
+                                },
+                                option_button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    get_button_text_style(asset_server),
+                                ));
+                            });
+                    }
+                });
+        })
+        .id()
+}
+
+fn spawn_option_arrow(
+    parent: &mut ChildBuilder,
+    asset_server: &Res<AssetServer>,
+    label: &str,
+    option_button: OptionButton,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: option_arrow_button_style(),
+                background_color: NORMAL_BUTTON_COLOR.into(),
+                ..default()
+            },
+            option_button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, get_button_text_style(asset_server)));
+        });
+}
+
+pub fn edit_world_seed(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut world_seed: ResMut<WorldSeed>,
+    mut world_map: ResMut<WorldMap>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
         }
-        GameState::Paused => {
-            return "sprites/Menu-Button.png";
+        let digit = match event.key_code {
+            KeyCode::Digit0 | KeyCode::Numpad0 => Some(0),
+            KeyCode::Digit1 | KeyCode::Numpad1 => Some(1),
+            KeyCode::Digit2 | KeyCode::Numpad2 => Some(2),
+            KeyCode::Digit3 | KeyCode::Numpad3 => Some(3),
+            KeyCode::Digit4 | KeyCode::Numpad4 => Some(4),
+            KeyCode::Digit5 | KeyCode::Numpad5 => Some(5),
+            KeyCode::Digit6 | KeyCode::Numpad6 => Some(6),
+            KeyCode::Digit7 | KeyCode::Numpad7 => Some(7),
+            KeyCode::Digit8 | KeyCode::Numpad8 => Some(8),
+            KeyCode::Digit9 | KeyCode::Numpad9 => Some(9),
+            _ => None,
+        };
+
+        if let Some(digit) = digit {
+            if let Some(seed) = world_seed
+                .seed
+                .checked_mul(10)
+                .and_then(|seed| seed.checked_add(digit))
+            {
+                world_seed.seed = seed;
+            }
+        } else if event.key_code == KeyCode::Backspace {
+            world_seed.seed /= 10;
+        } else {
+            continue;
         }
-        GameState::Game => {
-            return "sprites/Quit-Button.png";
+        // Typing a seed means generating from it
+        world_map.selected = false;
+    }
+}
+
+pub fn interact_world_buttons(
+    mut button_query: Query<(&Interaction, &mut BackgroundColor, &WorldButton), Changed<Interaction>>,
+    mut world_seed: ResMut<WorldSeed>,
+    mut world_generator: ResMut<WorldGenerator>,
+    mut world_map: ResMut<WorldMap>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut background_color, world_button) in button_query.iter_mut() {
+        match *interaction {
+            Interaction::Pressed => {
+                *background_color = PRESSED_BUTTON_COLOR.into();
+
+                match world_button {
+                    WorldButton::NewSeed => {
+                        *world_seed = WorldSeed::default();
+                        world_map.selected = false;
+                    }
+                    WorldButton::Generator => {
+                        world_generator.kind = world_generator.kind.next();
+                        world_map.selected = false;
+                    }
+                    WorldButton::Map => world_map.selected = !world_map.selected,
+                    WorldButton::Editor => game_state.set(GameState::Editor),
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVERED_BUTTON_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = NORMAL_BUTTON_COLOR.into();
+            }
+        }
+    }
+}
+
+pub fn refresh_seed_text(
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
+    mut seed_text_query: Query<&mut Text, With<SeedText>>,
+) {
+    if world_seed.is_changed() || world_generator.is_changed() || world_map.is_changed() {
+        for mut text in seed_text_query.iter_mut() {
+            text.sections[0].value = seed_label(&world_seed, &world_generator, &world_map);
         }
     }
 }
 
+fn seed_label(world_seed: &WorldSeed, world_generator: &WorldGenerator, world_map: &WorldMap) -> String {
+    match game_map_path(world_map) {
+        Some(path) => format!("Map: {}", path.display()),
+        None => format!(
+            "Seed: {}  Generator: {}  (0-9 edit)",
+            world_seed.seed,
+            world_generator.kind.name()
+        ),
+    }
+}
+
+fn world_button_label(world_button: WorldButton) -> &'static str {
+    match world_button {
+        WorldButton::NewSeed => "New Seed",
+        WorldButton::Generator => "Generator",
+        WorldButton::Map => "Map",
+        WorldButton::Editor => "Editor",
+    }
+}
+
+fn play_or_resume(game_state_const: &Res<State<GameState>>) -> &'static str {
+    match *game_state_const.get() {
+        GameState::Menu => "sprites/Play-Button.png",
+        GameState::Paused => "sprites/Resume-Button.png",
+        GameState::Game | GameState::Editor => "sprites/Play-Button.png",
+    }
+}
+
+fn quit_or_main_menu(game_state_const: &Res<State<GameState>>) -> &'static str {
+    match *game_state_const.get() {
+        GameState::Menu => "sprites/Quit-Button.png",
+        GameState::Paused => "sprites/Menu-Button.png",
+        GameState::Game | GameState::Editor => "sprites/Quit-Button.png",
+    }
+}
+
 fn build_main_menu(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     window_query: Query<&Window, With<PrimaryWindow>>,
     game_state_const: Res<State<GameState>>,
+    world_seed: &WorldSeed,
+    world_generator: &WorldGenerator,
+    world_map: &WorldMap,
 ) -> Entity {
     let main_menu_entity = commands
         .spawn((
@@ -276,7 +1096,7 @@ fn build_main_menu(
                             text: Text {
                                 sections: vec![TextSection::new(
                                     "Shoyu",
-                                    get_shadow_text_style(&asset_server),
+                                    get_shadow_text_style(asset_server),
                                 )],
                                 justify: JustifyText::Center,
                                 ..default()
@@ -289,7 +1109,7 @@ fn build_main_menu(
                         text: Text {
                             sections: vec![TextSection::new(
                                 "Shoyu",
-                                get_title_text_style(&asset_server),
+                                get_title_text_style(asset_server),
                             )],
                             justify: JustifyText::Center,
                             ..default()
@@ -297,6 +1117,25 @@ fn build_main_menu(
                         ..default()
                     });
                 });
+            // === Continue Button ===
+            if *game_state_const.get() == GameState::Menu && latest_slot().is_some() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: button_style(),
+                            background_color: NORMAL_BUTTON_COLOR.into(),
+                            ..default()
+                        },
+                        ContinueButton {},
+                        Focusable { order: 0 },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Continue",
+                            get_button_text_style(asset_server),
+                        ));
+                    });
+            }
             // === Play Button ===
             parent.spawn((
                 ButtonBundle {
@@ -309,6 +1148,7 @@ fn build_main_menu(
                     ..default()
                 },
                 PlayButton {},
+                Focusable { order: 1 },
             ));
             // === Options Button ===
             parent.spawn((
@@ -322,6 +1162,7 @@ fn build_main_menu(
                     ..default()
                 },
                 OptionsButton {},
+                Focusable { order: 2 },
             ));
             // === Quit Button ===
             parent.spawn((
@@ -335,21 +1176,114 @@ fn build_main_menu(
                     ..default()
                 },
                 QuitButton {},
+                Focusable { order: 3 },
             ));
+            // === Save Slots ===
+            if *game_state_const.get() != GameState::Game {
+                parent
+                    .spawn(NodeBundle {
+                        style: save_slot_row_style(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        let label = match *game_state_const.get() {
+                            GameState::Paused => "Save",
+                            _ => "Load",
+                        };
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(label, get_button_text_style(asset_server)),
+                            style: Style {
+                                margin: UiRect::right(Val::Px(10.0)),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                        for slot in 0..SAVE_SLOTS {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: save_slot_button_style(),
+                                        background_color: NORMAL_BUTTON_COLOR.into(),
+                                        ..default()
+                                    },
+                                    SaveSlotButton { slot },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        save_slot_label(slot, &game_state_const),
+                                        get_button_text_style(asset_server),
+                                    ));
+                                });
+                        }
+                    });
+            }
+            // === Seed ===
+            if *game_state_const.get() == GameState::Menu {
+                parent.spawn((
+                    TextBundle {
+                        text: Text::from_section(
+                            seed_label(world_seed, world_generator, world_map),
+                            TextStyle {
+                                font: asset_server.load("fonts/Righteous-Regular.ttf"),
+                                font_size: 30.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        style: Style {
+                            margin: UiRect::top(Val::Px(20.0)),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    SeedText {},
+                ));
+                // === World ===
+                parent
+                    .spawn(NodeBundle {
+                        style: save_slot_row_style(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        let mut buttons = vec![WorldButton::NewSeed, WorldButton::Generator];
+                        // Only offered once there is a map to play
+                        if editor_map_path(world_map).is_file() {
+                            buttons.push(WorldButton::Map);
+                        }
+                        buttons.push(WorldButton::Editor);
+                        for (index, world_button) in buttons.into_iter().enumerate() {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: world_button_style(),
+                                        background_color: NORMAL_BUTTON_COLOR.into(),
+                                        ..default()
+                                    },
+                                    world_button,
+                                    Focusable { order: 4 + index },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        world_button_label(world_button),
+                                        get_button_text_style(asset_server),
+                                    ));
+                                });
+                        }
+                    });
+            }
         })
         .id();
-    return main_menu_entity;
+    main_menu_entity
 }
 
 pub fn fps_system(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
     mut tracker: ResMut<FpsTracker>,
-    fps_query: Query<Entity, With<FPS>>,
+    fps_query: Query<Entity, With<Fps>>,
     time: Res<Time>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
 ) {
-    if keyboard_input.just_released(KeyCode::KeyN) {
+    if actions.just_released(Action::ToggleFps) {
         tracker.enabled = !tracker.enabled;
     }
 
@@ -362,7 +1296,7 @@ pub fn fps_system(
 
         let font = asset_server.load("fonts/Righteous-Regular.ttf");
         let text_style = TextStyle {
-            font: font,
+            font,
             font_size: 30.0,
             color: Color::WHITE,
         };
@@ -377,7 +1311,7 @@ pub fn fps_system(
                 },
                 ..default()
             },
-            FPS {},
+            Fps {},
         ));
     }
 }
@@ -385,12 +1319,12 @@ pub fn fps_system(
 pub fn draw_cords(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
     player_query: Query<&mut Transform, With<Player>>,
     mut tracker: ResMut<DrawCordsTracker>,
     cords_query: Query<Entity, With<Cords>>,
 ) {
-    if keyboard_input.just_released(KeyCode::KeyM) {
+    if actions.just_released(Action::ToggleCoords) {
         tracker.enabled = !tracker.enabled;
     }
     for cords_entity in cords_query.iter() {
@@ -401,7 +1335,7 @@ pub fn draw_cords(
         if let Ok(player_transform) = player_query.get_single() {
             let font = asset_server.load("fonts/Righteous-Regular.ttf");
             let text_style = TextStyle {
-                font: font,
+                font,
                 font_size: 30.0,
                 color: Color::WHITE,
             };
@@ -432,10 +1366,13 @@ pub fn draw_cords(
 pub fn zoom_camera_system(
     mut camera_query: Query<&mut OrthographicProjection, With<PlayerCamera>>,
     mut mouse_wheel_events: EventReader<MouseWheel>,
+    actions: Res<ButtonInput<Action>>,
+    analog_input: Res<AnalogInput>,
+    settings: Res<Settings>,
+    time: Res<Time>,
 ) {
     const ZOOM_SPEED: f32 = 0.1;
-    const MIN_ZOOM: f32 = 0.1;
-    const MAX_ZOOM: f32 = 5.0;
+    const KEY_ZOOM_SPEED: f32 = 1.5;
 
     let mut zoom_factor = 1.0;
     for event in mouse_wheel_events.read() {
@@ -445,8 +1382,28 @@ pub fn zoom_camera_system(
             zoom_factor += ZOOM_SPEED;
         }
     }
+    if actions.pressed(Action::ZoomIn) {
+        zoom_factor -= KEY_ZOOM_SPEED * time.delta_seconds();
+    }
+    if actions.pressed(Action::ZoomOut) {
+        zoom_factor += KEY_ZOOM_SPEED * time.delta_seconds();
+    }
+    zoom_factor += analog_input.zoom * KEY_ZOOM_SPEED * time.delta_seconds();
 
     for mut projection in camera_query.iter_mut() {
-        projection.scale = (projection.scale * zoom_factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        projection.scale = (projection.scale * zoom_factor).clamp(settings.min_zoom, settings.max_zoom);
+    }
+}
+
+// Keeps the camera inside the zoom limits when they are changed in the options menu
+pub fn clamp_camera_zoom(
+    mut camera_query: Query<&mut OrthographicProjection, With<PlayerCamera>>,
+    settings: Res<Settings>,
+) {
+    for mut projection in camera_query.iter_mut() {
+        let scale = projection.scale.clamp(settings.min_zoom, settings.max_zoom);
+        if projection.scale != scale {
+            projection.scale = scale;
+        }
     }
 }
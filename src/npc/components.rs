@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+use crate::pathfinding::resources::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Behavior {
+    Idle,
+    Wander { target: Vec2 },
+    Flee,
+    Follow,
+}
+
+#[derive(Component)]
+pub struct Npc {
+    // Index into the bestiary
+    pub creature: usize,
+    // Chunk it spawned in, it goes when that chunk unloads
+    pub home: IVec2,
+    pub behavior: Behavior,
+    // Seconds until an idle or wandering NPC picks something else to do
+    pub timer: f32,
+    // A wander waits for its path, then walks it tile by tile
+    pub route_request: Option<PathId>,
+    pub route: Vec<IVec2>,
+}
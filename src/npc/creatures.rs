@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::world::registry::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Temperament {
+    // Runs from the player
+    Skittish,
+    // Trails after the player
+    Tame,
+    // Wanders about whatever the player does
+    Calm,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpawnRule {
+    // Chunk biomes it lives in, any biome when empty
+    #[serde(default)]
+    pub biomes: Vec<TileType>,
+    // It starts next to one of these tiles, anywhere walkable when empty
+    #[serde(default)]
+    pub near_tiles: Vec<TileType>,
+    // Only chunks with one of these structures get any
+    #[serde(default)]
+    pub near_structures: Vec<StructureType>,
+    // Spots tried per chunk, each taken with the chance
+    pub attempts: u32,
+    pub chance: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Creature {
+    pub name: String,
+    // Relative to the assets folder
+    pub texture: String,
+    // Pixels per second while wandering
+    pub speed: f32,
+    pub temperament: Temperament,
+    // Tiles away the player is noticed from
+    pub sight: f32,
+    pub spawn: SpawnRule,
+}
+
+impl Creature {
+    fn undefined_names(&self) -> Vec<&'static str> {
+        let tiles = self
+            .spawn
+            .biomes
+            .iter()
+            .chain(&self.spawn.near_tiles)
+            .filter(|tile_type| !tile_type.is_defined())
+            .map(|tile_type| tile_type.name());
+        let structures = self
+            .spawn
+            .near_structures
+            .iter()
+            .filter(|structure_type| !structure_type.is_defined())
+            .map(|structure_type| structure_type.name());
+        tiles.chain(structures).collect()
+    }
+}
+
+pub fn creatures_path() -> PathBuf {
+    PathBuf::from("assets/creatures.ron")
+}
+
+// Loads after the tile and structure definitions. Creatures mentioning undefined
+// types are left out, each with the reason why.
+pub fn load_creatures() -> Result<(Vec<Creature>, Vec<String>), String> {
+    let loaded: Vec<Creature> = load_definitions(&creatures_path())?;
+    let mut creatures: Vec<Creature> = vec![];
+    let mut rejected: Vec<String> = vec![];
+    for creature in loaded {
+        let undefined = creature.undefined_names();
+        if creatures.iter().any(|known| known.name == creature.name) {
+            rejected.push(format!("{} is defined twice", creature.name));
+        } else if !undefined.is_empty() {
+            rejected.push(format!("{} mentions undefined {}", creature.name, undefined.join(", ")));
+        } else {
+            creatures.push(creature);
+        }
+    }
+    Ok((creatures, rejected))
+}
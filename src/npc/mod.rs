@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+mod components;
+mod creatures;
+mod resources;
+mod systems;
+mod utils;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bestiary>()
+            .init_resource::<NpcSpawns>()
+            // Spawn rules refer to tiles and structures, whose definitions load during Startup
+            .add_systems(PostStartup, load_bestiary)
+            .add_systems(OnEnter(GameState::Menu), despawn_npcs)
+            .add_systems(
+                Update,
+                (stream_npcs, update_npc_behavior, move_npcs)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+use crate::npc::creatures::*;
+
+#[derive(Resource, Default)]
+pub struct Bestiary {
+    pub creatures: Vec<Creature>,
+}
+
+// Loaded chunks whose NPCs have been spawned
+#[derive(Resource, Default)]
+pub struct NpcSpawns {
+    pub chunks: HashSet<IVec2>,
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::components::*;
+use crate::npc::components::*;
+use crate::npc::creatures::*;
+use crate::npc::resources::*;
+use crate::npc::utils::*;
+use crate::pathfinding::resources::*;
+use crate::player::utils::*;
+use crate::resources::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+// Running away is quicker than wandering, following a touch quicker so they keep up
+const FLEE_SPEED_FACTOR: f32 = 2.5;
+const FOLLOW_SPEED_FACTOR: f32 = 3.0;
+// Followers stop this many tiles from the player
+const FOLLOW_DISTANCE: f32 = 1.5;
+const NPC_BASE_OFFSET: f32 = -10.0;
+
+pub fn load_bestiary(mut bestiary: ResMut<Bestiary>) {
+    match load_creatures() {
+        Ok((creatures, rejected)) => {
+            for reason in rejected {
+                warn!("Skipping creature {}", reason);
+            }
+            bestiary.creatures = creatures;
+        }
+        Err(err) => error!("Failed to load creatures: {}", err),
+    }
+}
+
+// NPCs come and go with the chunk they spawned in
+pub fn stream_npcs(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    the_world: Res<TheWorld>,
+    loaded_chunks: Res<LoadedChunks>,
+    bestiary: Res<Bestiary>,
+    mut npc_spawns: ResMut<NpcSpawns>,
+    mut pathfinder: ResMut<Pathfinder>,
+    npc_query: Query<(Entity, &Npc)>,
+) {
+    for (npc_entity, npc) in npc_query.iter() {
+        if !loaded_chunks.chunks.contains_key(&npc.home) {
+            if let Some(route_request) = npc.route_request {
+                pathfinder.cancel(route_request);
+            }
+            commands.entity(npc_entity).despawn_recursive();
+        }
+    }
+    npc_spawns.chunks.retain(|chunk_pos| loaded_chunks.chunks.contains_key(chunk_pos));
+
+    for chunk_pos in loaded_chunks.chunks.keys() {
+        if !npc_spawns.chunks.insert(*chunk_pos) {
+            continue;
+        }
+        for (index, creature) in bestiary.creatures.iter().enumerate() {
+            let mut rng = chunk_npc_rng(the_world.seed, *chunk_pos, index);
+            for tile_pos in spawn_tiles(&the_world, *chunk_pos, creature, &mut rng) {
+                let position = tile_position(tile_pos);
+                commands.spawn((
+                    SpriteBundle {
+                        transform: Transform::from_xyz(position.x, position.y, Y_SORT_MIN_Z),
+                        texture: asset_server.load(&creature.texture),
+                        ..default()
+                    },
+                    Npc {
+                        creature: index,
+                        home: *chunk_pos,
+                        behavior: Behavior::Idle,
+                        timer: rng.gen_range(0.0..3.0),
+                        route_request: None,
+                        route: vec![],
+                    },
+                    YSort {
+                        base_offset: NPC_BASE_OFFSET,
+                        airborne: false,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn despawn_npcs(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    mut npc_spawns: ResMut<NpcSpawns>,
+    npc_query: Query<Entity, With<Npc>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        for npc_entity in npc_query.iter() {
+            commands.entity(npc_entity).despawn_recursive();
+        }
+        npc_spawns.chunks.clear();
+    }
+}
+
+// Picks what each NPC does next from its temperament and how close the player is
+pub fn update_npc_behavior(
+    the_world: Res<TheWorld>,
+    bestiary: Res<Bestiary>,
+    time: Res<Time>,
+    mut pathfinder: ResMut<Pathfinder>,
+    player_query: Query<(&Transform, &Player), Without<Npc>>,
+    mut npc_query: Query<(&Transform, &mut Npc)>,
+) {
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|(transform, player)| player_ground(transform, player));
+    let mut rng = thread_rng();
+    for (transform, mut npc) in npc_query.iter_mut() {
+        let Some(creature) = bestiary.creatures.get(npc.creature) else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let distance = player
+            .map(|player| player.distance(position) / TILE_SIZE)
+            .unwrap_or(f32::INFINITY);
+        npc.timer -= time.delta_seconds();
+        let behavior = next_behavior(&the_world, &mut npc, creature, position, distance, &mut rng);
+
+        if behavior != npc.behavior {
+            if let Some(route_request) = npc.route_request.take() {
+                pathfinder.cancel(route_request);
+            }
+            npc.route.clear();
+            if let Behavior::Wander { target } = behavior {
+                npc.route_request = Some(pathfinder.request(world_to_tile(position), world_to_tile(target)));
+            }
+        }
+        npc.behavior = behavior;
+    }
+}
+
+pub fn move_npcs(
+    the_world: Res<TheWorld>,
+    bestiary: Res<Bestiary>,
+    time: Res<Time>,
+    mut pathfinder: ResMut<Pathfinder>,
+    player_query: Query<(&Transform, &Player), Without<Npc>>,
+    mut npc_query: Query<(&mut Transform, &mut Sprite, &mut Npc)>,
+) {
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|(transform, player)| player_ground(transform, player));
+    for (mut transform, mut sprite, mut npc) in npc_query.iter_mut() {
+        let Some(creature) = bestiary.creatures.get(npc.creature) else {
+            continue;
+        };
+        let mut position = transform.translation.truncate();
+        if let Some(route_request) = npc.route_request {
+            match pathfinder.poll(route_request) {
+                PathStatus::Pending => continue,
+                PathStatus::Found(path) => {
+                    // The first tile is the one it is standing on
+                    npc.route = path.into_iter().skip(1).collect();
+                    npc.route_request = None;
+                }
+                PathStatus::NoPath => {
+                    npc.route_request = None;
+                    npc.behavior = Behavior::Idle;
+                    npc.timer = 1.0;
+                    continue;
+                }
+            }
+        }
+        if let Some(next) = npc.route.first() {
+            let waypoint = tile_position(*next);
+            if Vec2::new(waypoint.x, waypoint.y).distance(position) < 2.0 {
+                npc.route.remove(0);
+            }
+        }
+
+        let mut remaining = None;
+        let velocity = match (npc.behavior, player) {
+            (Behavior::Wander { target }, _) => {
+                let waypoint = npc.route.first().map(|next| tile_position(*next));
+                let waypoint = waypoint.map_or(target, |waypoint| Vec2::new(waypoint.x, waypoint.y));
+                remaining = Some(waypoint - position);
+                (waypoint - position).normalize_or_zero() * creature.speed
+            }
+            (Behavior::Flee, Some(player)) => {
+                (position - player).normalize_or_zero() * creature.speed * FLEE_SPEED_FACTOR
+            }
+            (Behavior::Follow, Some(player)) if player.distance(position) > FOLLOW_DISTANCE * TILE_SIZE => {
+                (player - position).normalize_or_zero() * creature.speed * FOLLOW_SPEED_FACTOR
+            }
+            _ => Vec2::ZERO,
+        };
+        if velocity == Vec2::ZERO {
+            continue;
+        }
+
+        let mut step = velocity * time.delta_seconds();
+        // Stops on the waypoint rather than past it, so long frames cannot swing it back and forth
+        if let Some(remaining) = remaining.filter(|remaining| remaining.length() < step.length()) {
+            step = remaining;
+        }
+        let moved = move_npc(&the_world, &mut position, step);
+        if !moved && matches!(npc.behavior, Behavior::Wander { .. }) {
+            // Walked into a dead end, rest before picking somewhere else
+            npc.behavior = Behavior::Idle;
+            npc.timer = 1.0;
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        if velocity.x != 0.0 {
+            sprite.flip_x = velocity.x < 0.0;
+        }
+    }
+}
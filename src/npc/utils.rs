@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::npc::components::*;
+use crate::npc::creatures::*;
+use crate::world::components::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+// Tiles from its current spot a wandering NPC heads for at most
+const WANDER_RANGE: i32 = 4;
+// Sight is multiplied by this before a fleeing or following NPC loses interest
+const GIVE_UP_FACTOR: f32 = 2.0;
+// Seconds a wander lasts at most before the NPC rests again
+const WANDER_TIMEOUT: f32 = 6.0;
+// Seconds before an NPC that found nowhere to wander tries again
+const WANDER_RETRY: f32 = 0.5;
+
+// The same chunk of the same world always gets the same NPCs
+pub fn chunk_npc_rng(seed: u64, chunk_pos: IVec2, creature: usize) -> StdRng {
+    let hash = (chunk_pos.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (chunk_pos.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (creature as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+    StdRng::seed_from_u64(seed ^ hash ^ 0x4E50_4353)
+}
+
+// Tiles in a loaded chunk where a creature spawns, following its spawn rule
+pub fn spawn_tiles(the_world: &TheWorld, chunk_pos: IVec2, creature: &Creature, rng: &mut StdRng) -> Vec<IVec2> {
+    let Some(chunk) = the_world.chunks.get(&chunk_pos) else {
+        return vec![];
+    };
+    let rule = &creature.spawn;
+    if !rule.biomes.is_empty() && !rule.biomes.contains(&chunk.biome) {
+        return vec![];
+    }
+    let has_structure = chunk.tiles.iter().flatten().any(|tile| match tile.structure {
+        Structure::Anchor(structure_type) => rule.near_structures.contains(&structure_type),
+        _ => false,
+    });
+    if !rule.near_structures.is_empty() && !has_structure {
+        return vec![];
+    }
+
+    // Picked from the tiles that fit, so a creature tied to a shore still finds the one strip of it
+    let origin = chunk_origin(chunk_pos);
+    let candidates: Vec<IVec2> = (0..CHUNK_SIZE)
+        .flat_map(|y| (0..CHUNK_SIZE).map(move |x| origin + IVec2::new(x, y)))
+        .filter(|tile_pos| the_world.is_walkable(*tile_pos))
+        .filter(|tile_pos| {
+            rule.near_tiles.is_empty()
+                || [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].iter().any(|step| {
+                    the_world
+                        .get_tile(*tile_pos + *step)
+                        .is_some_and(|tile| rule.near_tiles.contains(&tile.tile_type))
+                })
+        })
+        .collect();
+    if candidates.is_empty() {
+        return vec![];
+    }
+
+    let mut tiles: Vec<IVec2> = vec![];
+    for _ in 0..rule.attempts {
+        // Both rolls happen every attempt so one failing does not shift the rest
+        let taken = rng.gen::<f32>() < rule.chance;
+        let tile_pos = candidates[rng.gen_range(0..candidates.len())];
+        if taken && !tiles.contains(&tile_pos) {
+            tiles.push(tile_pos);
+        }
+    }
+    tiles
+}
+
+// What the NPC does next with the player this many tiles away, resetting its timer
+// whenever it starts resting or wandering
+pub fn next_behavior(
+    the_world: &TheWorld,
+    npc: &mut Npc,
+    creature: &Creature,
+    position: Vec2,
+    distance: f32,
+    rng: &mut impl Rng,
+) -> Behavior {
+    let noticed = match (creature.temperament, npc.behavior) {
+        (Temperament::Skittish, Behavior::Flee) | (Temperament::Tame, Behavior::Follow) => {
+            distance <= creature.sight * GIVE_UP_FACTOR
+        }
+        (Temperament::Calm, _) => false,
+        _ => distance <= creature.sight,
+    };
+    match (creature.temperament, npc.behavior) {
+        (Temperament::Skittish, _) if noticed => Behavior::Flee,
+        (Temperament::Tame, _) if noticed => Behavior::Follow,
+        (_, Behavior::Flee | Behavior::Follow) => {
+            npc.timer = rng.gen_range(1.0..3.0);
+            Behavior::Idle
+        }
+        (_, Behavior::Idle) if npc.timer <= 0.0 => match wander_target(the_world, position, rng) {
+            Some(target) => {
+                npc.timer = WANDER_TIMEOUT;
+                Behavior::Wander { target }
+            }
+            // Nowhere to go this time, try again shortly
+            None => {
+                npc.timer = WANDER_RETRY;
+                Behavior::Idle
+            }
+        },
+        (_, Behavior::Wander { target }) if npc.timer <= 0.0 || target.distance(position) < 2.0 => {
+            npc.timer = rng.gen_range(1.5..5.0);
+            Behavior::Idle
+        }
+        (_, behavior) => behavior,
+    }
+}
+
+pub fn wander_target(the_world: &TheWorld, from: Vec2, rng: &mut impl Rng) -> Option<Vec2> {
+    let tile_pos = world_to_tile(from)
+        + IVec2::new(
+            rng.gen_range(-WANDER_RANGE..=WANDER_RANGE),
+            rng.gen_range(-WANDER_RANGE..=WANDER_RANGE),
+        );
+    let target = tile_position(tile_pos);
+    the_world.is_walkable(tile_pos).then_some(Vec2::new(target.x, target.y))
+}
+
+// Moves each axis on its own so NPCs slide along water and trees instead of sticking.
+// Returns false when blocked on both.
+pub fn move_npc(the_world: &TheWorld, position: &mut Vec2, movement: Vec2) -> bool {
+    let mut moved = false;
+    for step in [Vec2::new(movement.x, 0.0), Vec2::new(0.0, movement.y)] {
+        if step == Vec2::ZERO {
+            continue;
+        }
+        let next = *position + step;
+        if the_world.is_walkable(world_to_tile(next)) {
+            *position = next;
+            moved = true;
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::registry::*;
+    use crate::world::tiles::*;
+    use crate::world::structures::*;
+
+    fn grass_world() -> TheWorld {
+        let grass = Tile {
+            tile_type: TileType::named("Grass"),
+            ..default()
+        };
+        let mut the_world = TheWorld::default();
+        the_world.chunks.insert(
+            IVec2::ZERO,
+            Chunk {
+                biome: TileType::named("Grass"),
+                tiles: vec![vec![grass; CHUNK_SIZE as usize]; CHUNK_SIZE as usize],
+            },
+        );
+        the_world
+    }
+
+    fn creature(temperament: Temperament, spawn: SpawnRule) -> Creature {
+        Creature {
+            name: "Test".to_string(),
+            texture: String::new(),
+            speed: 30.0,
+            temperament,
+            sight: 3.0,
+            spawn,
+        }
+    }
+
+    fn spawn_rule(near_tiles: Vec<TileType>) -> SpawnRule {
+        SpawnRule {
+            biomes: vec![],
+            near_tiles,
+            near_structures: vec![],
+            attempts: 40,
+            chance: 1.0,
+        }
+    }
+
+    fn idle_npc(timer: f32) -> Npc {
+        Npc {
+            creature: 0,
+            home: IVec2::ZERO,
+            behavior: Behavior::Idle,
+            timer,
+            route_request: None,
+            route: vec![],
+        }
+    }
+
+    #[test]
+    fn chunk_rolls_are_repeatable() {
+        let roll = |seed, chunk_pos, creature| chunk_npc_rng(seed, chunk_pos, creature).gen::<u64>();
+        assert_eq!(roll(1, IVec2::new(2, -3), 0), roll(1, IVec2::new(2, -3), 0));
+        assert_ne!(roll(1, IVec2::new(2, -3), 0), roll(1, IVec2::new(2, -3), 1));
+        assert_ne!(roll(1, IVec2::new(2, -3), 0), roll(1, IVec2::new(-3, 2), 0));
+        assert_ne!(roll(1, IVec2::new(2, -3), 0), roll(2, IVec2::new(2, -3), 0));
+    }
+
+    #[test]
+    fn spawns_follow_the_spawn_rule() {
+        let mut the_world = grass_world();
+        let pond = IVec2::new(5, 5);
+        let mut water = *the_world.get_tile(pond).unwrap();
+        water.tile_type = TileType::named("Water");
+        the_world.set_tile(pond, water);
+
+        let crab = creature(Temperament::Skittish, spawn_rule(vec![TileType::named("Water")]));
+        let tiles = spawn_tiles(&the_world, IVec2::ZERO, &crab, &mut chunk_npc_rng(9, IVec2::ZERO, 0));
+        assert!(!tiles.is_empty());
+        assert!(tiles.iter().all(|tile_pos| {
+            let offset = (*tile_pos - pond).abs();
+            offset.x + offset.y == 1
+        }));
+        // Every roll of the same chunk spawns the same way
+        assert_eq!(
+            tiles,
+            spawn_tiles(&the_world, IVec2::ZERO, &crab, &mut chunk_npc_rng(9, IVec2::ZERO, 0))
+        );
+
+        let mut elsewhere = crab.clone();
+        elsewhere.spawn.biomes = vec![TileType::named("Ground")];
+        assert!(spawn_tiles(&the_world, IVec2::ZERO, &elsewhere, &mut chunk_npc_rng(9, IVec2::ZERO, 0)).is_empty());
+        let mut nesting = crab.clone();
+        nesting.spawn.near_structures = vec![StructureType::named("Palm")];
+        assert!(spawn_tiles(&the_world, IVec2::ZERO, &nesting, &mut chunk_npc_rng(9, IVec2::ZERO, 0)).is_empty());
+        assert!(spawn_tiles(&the_world, IVec2::ONE, &crab, &mut chunk_npc_rng(9, IVec2::ONE, 0)).is_empty());
+    }
+
+    #[test]
+    fn temperaments_react_to_the_player() {
+        let the_world = grass_world();
+        let position = Vec2::new(tile_position(IVec2::new(10, 10)).x, tile_position(IVec2::new(10, 10)).y);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut next = |temperament, npc: &mut Npc, distance| {
+            next_behavior(&the_world, npc, &creature(temperament, spawn_rule(vec![])), position, distance, &mut rng)
+        };
+
+        let mut npc = idle_npc(1.0);
+        assert_eq!(next(Temperament::Skittish, &mut npc, 2.0), Behavior::Flee);
+        assert_eq!(next(Temperament::Tame, &mut npc, 2.0), Behavior::Follow);
+        assert_eq!(next(Temperament::Calm, &mut npc, 0.5), Behavior::Idle);
+
+        // Fleeing and following carry on past sight, then give up and rest
+        npc.behavior = Behavior::Flee;
+        assert_eq!(next(Temperament::Skittish, &mut npc, 5.0), Behavior::Flee);
+        assert_eq!(next(Temperament::Skittish, &mut npc, 7.0), Behavior::Idle);
+        assert!((1.0..3.0).contains(&npc.timer));
+        npc.behavior = Behavior::Follow;
+        assert_eq!(next(Temperament::Tame, &mut npc, 5.0), Behavior::Follow);
+        assert_eq!(next(Temperament::Tame, &mut npc, 7.0), Behavior::Idle);
+    }
+
+    #[test]
+    fn rested_npcs_wander_or_retry_shortly() {
+        let position = Vec2::new(tile_position(IVec2::new(10, 10)).x, tile_position(IVec2::new(10, 10)).y);
+        let calm = creature(Temperament::Calm, spawn_rule(vec![]));
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut npc = idle_npc(0.0);
+        let behavior = next_behavior(&grass_world(), &mut npc, &calm, position, f32::INFINITY, &mut rng);
+        assert!(matches!(behavior, Behavior::Wander { .. }));
+        assert_eq!(npc.timer, WANDER_TIMEOUT);
+
+        // Nothing around it is walkable
+        let mut npc = idle_npc(0.0);
+        let behavior = next_behavior(&TheWorld::default(), &mut npc, &calm, position, f32::INFINITY, &mut rng);
+        assert_eq!(behavior, Behavior::Idle);
+        assert_eq!(npc.timer, WANDER_RETRY);
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+pub mod resources;
+pub mod search;
+mod systems;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pathfinder>()
+            .add_systems(OnEnter(GameState::Game), clear_paths)
+            .add_systems(
+                Update,
+                (invalidate_paths, run_path_searches)
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::collections::VecDeque;
+
+use crate::pathfinding::search::*;
+
+// Tiles expanded across all searches each frame, the rest carry over to the next one
+pub const SEARCH_BUDGET_PER_FRAME: usize = 2_000;
+// The cache is emptied once it holds this many paths
+const MAX_CACHED_PATHS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathId(u64);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStatus {
+    Pending,
+    // Every tile from the start to the goal, both included
+    Found(Vec<IVec2>),
+    // Also what cancelled and already collected requests report
+    NoPath,
+}
+
+// Queues path requests and works through them a budget of tiles at a time, so a
+// search across the whole world spreads over several frames instead of stalling one.
+// Found paths are kept until a tile their search looked at changes.
+#[derive(Resource, Default)]
+pub struct Pathfinder {
+    next_id: u64,
+    pending: VecDeque<(PathId, PathSearch)>,
+    finished: HashMap<PathId, Option<Vec<IVec2>>>,
+    cache: HashMap<(IVec2, IVec2), CachedPath>,
+}
+
+struct CachedPath {
+    path: Vec<IVec2>,
+    // Where the search that found the path looked
+    region: IRect,
+}
+
+impl Pathfinder {
+    pub fn request(&mut self, start: IVec2, goal: IVec2) -> PathId {
+        let id = PathId(self.next_id);
+        self.next_id += 1;
+        match self.cache.get(&(start, goal)) {
+            Some(cached) => {
+                self.finished.insert(id, Some(cached.path.clone()));
+            }
+            None => self.pending.push_back((id, PathSearch::new(start, goal))),
+        }
+        id
+    }
+
+    // Finished results are handed out once
+    pub fn poll(&mut self, id: PathId) -> PathStatus {
+        if self.pending.iter().any(|(pending_id, _)| *pending_id == id) {
+            return PathStatus::Pending;
+        }
+        match self.finished.remove(&id).flatten() {
+            Some(path) => PathStatus::Found(path),
+            None => PathStatus::NoPath,
+        }
+    }
+
+    pub fn cancel(&mut self, id: PathId) {
+        self.pending.retain(|(pending_id, _)| *pending_id != id);
+        self.finished.remove(&id);
+    }
+
+    // Works on the oldest request first so nothing waits forever behind newer ones
+    pub fn run(&mut self, grid: &impl PathGrid, budget: usize) {
+        let mut budget = budget;
+        while budget > 0 {
+            let Some((_, search)) = self.pending.front_mut() else {
+                return;
+            };
+            budget -= search.step(grid, budget).min(budget);
+            let result = match search.status() {
+                SearchStatus::Searching => continue,
+                SearchStatus::Found(path) => Some(path.clone()),
+                SearchStatus::NoPath => None,
+            };
+            let (id, search) = self.pending.pop_front().unwrap();
+            if let Some(path) = &result {
+                if self.cache.len() >= MAX_CACHED_PATHS {
+                    self.cache.clear();
+                }
+                self.cache.insert(
+                    (search.start, search.goal),
+                    CachedPath {
+                        path: path.clone(),
+                        region: search.region(),
+                    },
+                );
+            }
+            self.finished.insert(id, result);
+        }
+    }
+
+    // Drops cached paths whose search looked at a changed tile and restarts searches
+    // that had already seen one. A blocked tile opening up can make a shorter path.
+    pub fn invalidate(&mut self, tiles: &[IVec2]) {
+        if tiles.is_empty() {
+            return;
+        }
+        self.cache
+            .retain(|_, cached| !tiles.iter().any(|changed| cached.region.contains(*changed)));
+        for (_, search) in self.pending.iter_mut() {
+            if tiles.iter().any(|changed| search.has_seen(*changed)) {
+                search.restart();
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.finished.clear();
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::search::tests::TestGrid;
+
+    fn finish(pathfinder: &mut Pathfinder, grid: &TestGrid, id: PathId) -> PathStatus {
+        for _ in 0..100 {
+            match pathfinder.poll(id) {
+                PathStatus::Pending => pathfinder.run(grid, 3),
+                status => return status,
+            }
+        }
+        panic!("the search never finished");
+    }
+
+    #[test]
+    fn requests_finish_over_several_runs() {
+        let grid = TestGrid::new(&["S.........", "#########.", "G........."]);
+        let mut pathfinder = Pathfinder::default();
+        let id = pathfinder.request(grid.find('S'), grid.find('G'));
+        pathfinder.run(&grid, 3);
+        assert_eq!(pathfinder.poll(id), PathStatus::Pending);
+        let PathStatus::Found(path) = finish(&mut pathfinder, &grid, id) else {
+            panic!("no path found");
+        };
+        assert_eq!(path.last(), Some(&grid.find('G')));
+        // Handed out once only
+        assert_eq!(pathfinder.poll(id), PathStatus::NoPath);
+    }
+
+    #[test]
+    fn found_paths_are_cached_until_a_tile_along_them_changes() {
+        let grid = TestGrid::new(&["S...G"]);
+        let mut pathfinder = Pathfinder::default();
+        let id = pathfinder.request(grid.find('S'), grid.find('G'));
+        finish(&mut pathfinder, &grid, id);
+
+        let cached = pathfinder.request(grid.find('S'), grid.find('G'));
+        assert!(matches!(pathfinder.poll(cached), PathStatus::Found(_)));
+
+        // Two tiles above the path is too far away to matter
+        pathfinder.invalidate(&[IVec2::new(2, 2)]);
+        let cached = pathfinder.request(grid.find('S'), grid.find('G'));
+        assert!(matches!(pathfinder.poll(cached), PathStatus::Found(_)));
+
+        pathfinder.invalidate(&[IVec2::new(2, 1)]);
+        let searched = pathfinder.request(grid.find('S'), grid.find('G'));
+        assert_eq!(pathfinder.poll(searched), PathStatus::Pending);
+    }
+
+    #[test]
+    fn cached_detours_are_dropped_when_a_wall_they_went_around_opens() {
+        let grid = TestGrid::new(&["S#G", "...", "..."]);
+        let mut pathfinder = Pathfinder::default();
+        let id = pathfinder.request(grid.find('S'), grid.find('G'));
+        finish(&mut pathfinder, &grid, id);
+
+        pathfinder.invalidate(&[IVec2::new(1, 2)]);
+        let searched = pathfinder.request(grid.find('S'), grid.find('G'));
+        assert_eq!(pathfinder.poll(searched), PathStatus::Pending);
+    }
+
+    #[test]
+    fn cancelled_requests_are_dropped() {
+        let grid = TestGrid::new(&["S...G"]);
+        let mut pathfinder = Pathfinder::default();
+        let id = pathfinder.request(grid.find('S'), grid.find('G'));
+        pathfinder.cancel(id);
+        pathfinder.run(&grid, 100);
+        assert_eq!(pathfinder.poll(id), PathStatus::NoPath);
+        assert!(pathfinder.pending.is_empty() && pathfinder.finished.is_empty());
+    }
+}
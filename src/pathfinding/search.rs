@@ -0,0 +1,345 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::world::resources::*;
+
+// Searches give up after expanding this many tiles, about every tile of a fully streamed world
+pub const MAX_SEARCH_TILES: usize = 20_000;
+
+const STRAIGHT_STEPS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+const DIAGONAL_STEPS: [IVec2; 4] = [
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+// Anything searched over, the world or a hand-built grid in tests
+pub trait PathGrid {
+    // Cost of stepping onto the tile, at least 1.0, or None when it cannot be entered
+    fn cost(&self, tile_pos: IVec2) -> Option<f32>;
+}
+
+impl PathGrid for TheWorld {
+    fn cost(&self, tile_pos: IVec2) -> Option<f32> {
+        self.is_walkable(tile_pos)
+            .then(|| self.get_tile(tile_pos).map_or(1.0, |tile| tile.tile_type.move_cost().max(1.0)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchStatus {
+    Searching,
+    // Every tile from the start to the goal, both included
+    Found(Vec<IVec2>),
+    NoPath,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OpenTile {
+    estimate: f32,
+    cost: f32,
+    tile_pos: IVec2,
+}
+
+impl PartialEq for OpenTile {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenTile {}
+
+impl PartialOrd for OpenTile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Reversed so the heap pops the lowest estimate, ties going to the tile furthest along
+impl Ord for OpenTile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then(self.cost.total_cmp(&other.cost))
+            .then(self.tile_pos.to_array().cmp(&other.tile_pos.to_array()))
+    }
+}
+
+// An A* search that can be run a few tiles at a time. Diagonal steps are allowed
+// only when both tiles beside them are open, so paths never cut a corner.
+#[derive(Debug, Clone)]
+pub struct PathSearch {
+    pub start: IVec2,
+    pub goal: IVec2,
+    open: BinaryHeap<OpenTile>,
+    came_from: HashMap<IVec2, IVec2>,
+    best_cost: HashMap<IVec2, f32>,
+    // Tiles whose neighbours were looked at, blocked ones included
+    expanded: HashSet<IVec2>,
+    bounds: Option<IRect>,
+    status: SearchStatus,
+}
+
+impl PathSearch {
+    pub fn new(start: IVec2, goal: IVec2) -> Self {
+        let mut search = PathSearch {
+            start,
+            goal,
+            open: BinaryHeap::new(),
+            came_from: HashMap::new(),
+            best_cost: HashMap::new(),
+            expanded: HashSet::new(),
+            bounds: None,
+            status: SearchStatus::Searching,
+        };
+        search.restart();
+        search
+    }
+
+    pub fn restart(&mut self) {
+        self.open.clear();
+        self.came_from.clear();
+        self.best_cost.clear();
+        self.expanded.clear();
+        self.bounds = None;
+        self.status = SearchStatus::Searching;
+        self.open.push(OpenTile {
+            estimate: octile_distance(self.start, self.goal),
+            cost: 0.0,
+            tile_pos: self.start,
+        });
+        self.best_cost.insert(self.start, 0.0);
+    }
+
+    pub fn status(&self) -> &SearchStatus {
+        &self.status
+    }
+
+    // Whether the search has looked at the tile, so changing it could change the result.
+    // That is any neighbour of an expanded tile, since a blocked one may open up.
+    pub fn has_seen(&self, tile_pos: IVec2) -> bool {
+        tile_pos == self.goal
+            || (-1..=1).any(|dy| (-1..=1).any(|dx| self.expanded.contains(&(tile_pos + IVec2::new(dx, dy)))))
+    }
+
+    // Covers every tile the search has seen, changes outside it cannot change the result
+    pub fn region(&self) -> IRect {
+        let goal = IRect::from_corners(self.goal, self.goal);
+        match self.bounds {
+            Some(bounds) => IRect::from_corners(bounds.min - IVec2::ONE, bounds.max + IVec2::ONE).union(goal),
+            None => goal.union_point(self.start),
+        }
+    }
+
+    // Expands up to `budget` tiles and returns how many it used
+    pub fn step(&mut self, grid: &impl PathGrid, budget: usize) -> usize {
+        if self.status != SearchStatus::Searching {
+            return 0;
+        }
+        // The start may be blocked, the player can stand half on a tree, but the goal has to be reachable
+        if grid.cost(self.goal).is_none() && self.start != self.goal {
+            self.status = SearchStatus::NoPath;
+            return 0;
+        }
+
+        let mut used = 0;
+        while used < budget {
+            let Some(current) = self.open.pop() else {
+                self.status = SearchStatus::NoPath;
+                return used;
+            };
+            if self.best_cost.get(&current.tile_pos).is_some_and(|best| *best < current.cost) {
+                // Already reached more cheaply through another tile
+                continue;
+            }
+            if current.tile_pos == self.goal {
+                self.status = SearchStatus::Found(self.trace(self.goal));
+                return used;
+            }
+            used += 1;
+            self.expanded.insert(current.tile_pos);
+            self.bounds = Some(match self.bounds {
+                Some(bounds) => bounds.union_point(current.tile_pos),
+                None => IRect::from_corners(current.tile_pos, current.tile_pos),
+            });
+            if self.expanded.len() > MAX_SEARCH_TILES {
+                self.status = SearchStatus::NoPath;
+                return used;
+            }
+
+            for (next, step_cost) in neighbours(grid, current.tile_pos) {
+                let cost = current.cost + step_cost;
+                if self.best_cost.get(&next).is_some_and(|best| *best <= cost) {
+                    continue;
+                }
+                self.best_cost.insert(next, cost);
+                self.came_from.insert(next, current.tile_pos);
+                self.open.push(OpenTile {
+                    estimate: cost + octile_distance(next, self.goal),
+                    cost,
+                    tile_pos: next,
+                });
+            }
+        }
+        used
+    }
+
+    fn trace(&self, goal: IVec2) -> Vec<IVec2> {
+        let mut path = vec![goal];
+        let mut current = goal;
+        while let Some(previous) = self.came_from.get(&current) {
+            path.push(*previous);
+            current = *previous;
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn neighbours(grid: &impl PathGrid, tile_pos: IVec2) -> Vec<(IVec2, f32)> {
+    let mut neighbours: Vec<(IVec2, f32)> = STRAIGHT_STEPS
+        .iter()
+        .filter_map(|step| grid.cost(tile_pos + *step).map(|cost| (tile_pos + *step, cost)))
+        .collect();
+    for step in DIAGONAL_STEPS {
+        let beside_open = grid.cost(tile_pos + IVec2::new(step.x, 0)).is_some()
+            && grid.cost(tile_pos + IVec2::new(0, step.y)).is_some();
+        if let Some(cost) = grid.cost(tile_pos + step).filter(|_| beside_open) {
+            neighbours.push((tile_pos + step, cost * std::f32::consts::SQRT_2));
+        }
+    }
+    neighbours
+}
+
+// Exact for a grid of cost 1.0 tiles, which no tile is cheaper than
+fn octile_distance(from: IVec2, to: IVec2) -> f32 {
+    let delta = (to - from).abs();
+    let (long, short) = (delta.max_element() as f32, delta.min_element() as f32);
+    long - short + short * std::f32::consts::SQRT_2
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    // Rows from the top, '#' is blocked, a digit is a tile of that cost, anything else costs 1.0
+    pub struct TestGrid {
+        rows: Vec<Vec<char>>,
+    }
+
+    impl TestGrid {
+        pub fn new(rows: &[&str]) -> Self {
+            TestGrid {
+                rows: rows.iter().map(|row| row.chars().collect()).collect(),
+            }
+        }
+
+        // Positions count up from the bottom row, like the world
+        pub fn find(&self, marker: char) -> IVec2 {
+            for (row, chars) in self.rows.iter().enumerate() {
+                if let Some(x) = chars.iter().position(|c| *c == marker) {
+                    return IVec2::new(x as i32, (self.rows.len() - 1 - row) as i32);
+                }
+            }
+            panic!("no {} in the grid", marker);
+        }
+    }
+
+    impl PathGrid for TestGrid {
+        fn cost(&self, tile_pos: IVec2) -> Option<f32> {
+            if tile_pos.x < 0 || tile_pos.y < 0 || tile_pos.y as usize >= self.rows.len() {
+                return None;
+            }
+            let row = &self.rows[self.rows.len() - 1 - tile_pos.y as usize];
+            match row.get(tile_pos.x as usize)? {
+                '#' => None,
+                c => Some(c.to_digit(10).map_or(1.0, |cost| cost as f32)),
+            }
+        }
+    }
+
+    // Runs a search to the end in one go
+    fn find_path(grid: &impl PathGrid, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+        let mut search = PathSearch::new(start, goal);
+        while *search.status() == SearchStatus::Searching {
+            search.step(grid, MAX_SEARCH_TILES);
+        }
+        match search.status {
+            SearchStatus::Found(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    fn path(rows: &[&str]) -> Option<Vec<IVec2>> {
+        let grid = TestGrid::new(rows);
+        find_path(&grid, grid.find('S'), grid.find('G'))
+    }
+
+    #[test]
+    fn open_ground_is_crossed_diagonally() {
+        let path = path(&["...G", "....", "S..."]).unwrap();
+        assert_eq!(path.first(), Some(&IVec2::new(0, 0)));
+        assert_eq!(path.last(), Some(&IVec2::new(3, 2)));
+        assert_eq!(path.len(), 4);
+    }
+
+    #[test]
+    fn walls_are_walked_around() {
+        let path = path(&["S.#..", "..#..", "....G"]).unwrap();
+        assert!(path.iter().all(|tile_pos| *tile_pos != IVec2::new(2, 2) && *tile_pos != IVec2::new(2, 1)));
+        assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+    }
+
+    #[test]
+    fn corners_are_not_cut() {
+        // The only diagonal squeezes between two blocked tiles
+        assert_eq!(path(&["#G", "S#"]), None);
+        let found = path(&[".G", "S#"]).unwrap();
+        assert_eq!(found, vec![IVec2::new(0, 0), IVec2::new(0, 1), IVec2::new(1, 1)]);
+    }
+
+    #[test]
+    fn costly_tiles_are_avoided() {
+        let found = path(&["S9G", "..."]).unwrap();
+        assert!(!found.contains(&IVec2::new(1, 1)));
+        let found = path(&["S2G", "###"]).unwrap();
+        assert_eq!(found.len(), 3);
+    }
+
+    #[test]
+    fn enclosed_goals_have_no_path() {
+        assert_eq!(path(&["S.#.", "..#G", "..#."]), None);
+        assert_eq!(path(&["S.#", "..G"]).map(|path| path.len()), Some(3));
+    }
+
+    #[test]
+    fn blocked_neighbours_count_as_seen() {
+        let grid = TestGrid::new(&["S#G", "...", "...", "..."]);
+        let mut search = PathSearch::new(grid.find('S'), grid.find('G'));
+        search.step(&grid, MAX_SEARCH_TILES);
+        assert!(matches!(search.status(), SearchStatus::Found(_)));
+        // The wall was never entered, but opening it would make a shorter path
+        assert!(search.has_seen(IVec2::new(1, 3)));
+        assert!(search.region().contains(IVec2::new(1, 3)));
+        assert!(!search.has_seen(IVec2::new(1, 0)));
+    }
+
+    #[test]
+    fn budgeted_searches_match_a_single_run() {
+        let rows = ["S...#.....", ".##.#.###.", "..#...#...", "#.#####.#.", "........#G"];
+        let grid = TestGrid::new(&rows);
+        let expected = find_path(&grid, grid.find('S'), grid.find('G'));
+        let mut search = PathSearch::new(grid.find('S'), grid.find('G'));
+        let mut steps = 0;
+        while *search.status() == SearchStatus::Searching {
+            assert!(search.step(&grid, 2) <= 2);
+            steps += 1;
+        }
+        assert!(steps > 1);
+        assert_eq!(search.status(), &SearchStatus::Found(expected.unwrap()));
+    }
+}
@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+
+use crate::pathfinding::resources::*;
+use crate::resources::*;
+use crate::world::resources::*;
+
+pub fn clear_paths(mut reader: EventReader<GameStart>, mut pathfinder: ResMut<Pathfinder>) {
+    if let Some(_game_start) = reader.read().last() {
+        pathfinder.clear();
+    }
+}
+
+pub fn invalidate_paths(mut reader: EventReader<TilesChanged>, mut pathfinder: ResMut<Pathfinder>) {
+    for tiles_changed in reader.read() {
+        pathfinder.invalidate(&tiles_changed.tiles);
+    }
+}
+
+pub fn run_path_searches(the_world: Res<TheWorld>, mut pathfinder: ResMut<Pathfinder>) {
+    pathfinder.run(&*the_world, SEARCH_BUDGET_PER_FRAME);
+}
@@ -1 +1,14 @@
-//use bevy::prelude::*;
+use bevy::prelude::*;
+
+// Progress bar over the structure being harvested, the fill is its child
+#[derive(Component)]
+pub struct HarvestBar {}
+
+#[derive(Component)]
+pub struct HarvestBarFill {}
+
+#[derive(Component)]
+pub struct DestinationMarker {}
+
+#[derive(Component)]
+pub struct RouteMarker {}
@@ -3,8 +3,11 @@ use bevy::prelude::*;
 mod components;
 mod resources;
 mod systems;
+pub mod utils;
 
+use crate::building::systems::pick_build_target;
 use crate::resources::*;
+use resources::*;
 use systems::*;
 
 pub struct PlayerPlugin;
@@ -12,7 +15,20 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Game), spawn_player)
-            .add_systems(OnEnter(GameState::Menu), despawn_player)
-            .add_systems(Update, player_movement.run_if(in_state(GameState::Game)));
+            .add_systems(OnEnter(GameState::Menu), (despawn_player, reset_click_move))
+            .init_resource::<HarvestProgress>()
+            .init_resource::<ClickMove>()
+            .add_systems(
+                Update,
+                // Click-to-move only takes clicks the building systems had no use for
+                (
+                    plan_click_move.after(pick_build_target),
+                    player_movement,
+                    show_click_move,
+                    harvest_structures,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Game).and_then(in_state(CraftingMenuState::Closed))),
+            );
     }
 }
@@ -1 +1,57 @@
-//use bevy::prelude::*;
\ No newline at end of file
+use bevy::prelude::*;
+
+use crate::pathfinding::resources::*;
+use crate::world::utils::*;
+
+// Pixels from a waypoint that count as having reached it
+const WAYPOINT_REACHED: f32 = 4.0;
+// Seconds a refused destination stays marked
+pub const REFUSED_MARKER_TIME: f32 = 0.6;
+
+// Resets whenever the interact action is released or the target changes
+#[derive(Resource, Default)]
+pub struct HarvestProgress {
+    pub target: Option<IVec2>,
+    pub elapsed: f32,
+}
+
+// Where a click sent the player, and the tiles still to walk once the path is found
+#[derive(Resource, Default)]
+pub struct ClickMove {
+    pub destination: Option<IVec2>,
+    pub request: Option<PathId>,
+    pub route: Vec<IVec2>,
+    // A destination that could not be reached and the seconds its marker has left
+    pub refused: Option<(IVec2, f32)>,
+}
+
+impl ClickMove {
+    pub fn cancel(&mut self, pathfinder: &mut Pathfinder) {
+        if let Some(request) = self.request.take() {
+            pathfinder.cancel(request);
+        }
+        self.destination = None;
+        self.route.clear();
+    }
+
+    pub fn refuse(&mut self, tile_pos: IVec2, pathfinder: &mut Pathfinder) {
+        self.cancel(pathfinder);
+        self.refused = Some((tile_pos, REFUSED_MARKER_TIME));
+    }
+
+    // The centre of the next tile to walk to, dropping the ones already reached
+    pub fn next_waypoint(&mut self, ground: Vec2) -> Option<Vec2> {
+        while let Some(next) = self.route.first() {
+            let position = tile_position(*next);
+            let waypoint = Vec2::new(position.x, position.y);
+            if waypoint.distance(ground) > WAYPOINT_REACHED {
+                return Some(waypoint);
+            }
+            self.route.remove(0);
+        }
+        if self.request.is_none() {
+            self.destination = None;
+        }
+        None
+    }
+}
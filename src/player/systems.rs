@@ -1,32 +1,68 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::PrimaryWindow};
+use crate::building::resources::*;
 use crate::components::*;
+use crate::controls::resources::*;
 use crate::resources::*;
+use crate::player::utils::*;
+use crate::save::resources::*;
+use crate::inventory::components::*;
+use crate::player::components::*;
+use crate::pathfinding::resources::*;
+use crate::player::resources::*;
+use crate::settings::resources::*;
+use crate::weather::resources::*;
+use crate::world::resources::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
 
 pub const PLAYER_SPEED: i32 = 1000;
 const JUMP_SCALE: f32 = 1.2;
 const JUMP_DURATION: f32 = 0.5;
 const JUMP_PEAK_HEIGHT: f32 = 5.0 * 32.0;
 const JUMP_HALF_DURATION: f32 = JUMP_DURATION / 2.0;
+const HARVEST_BAR_WIDTH: f32 = 28.0;
+const HARVEST_BAR_HEIGHT: f32 = 4.0;
+const DESTINATION_COLOR: Color = Color::rgba(1.0, 0.9, 0.3, 0.6);
+const REFUSED_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.6);
+const ROUTE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.45);
+const DESTINATION_MARKER_SIZE: f32 = 14.0;
+const ROUTE_MARKER_SIZE: f32 = 5.0;
 
 pub fn spawn_player(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut reader: EventReader<GameStart>,
+    pending_load: Option<Res<PendingLoad>>,
 ) {
     if let Some(_game_start) = reader.read().last() {
+        let (transform, jump, inventory) = match pending_load {
+            Some(pending_load) => (
+                pending_load.save.player.transform(),
+                pending_load.save.player.jump.clone(),
+                pending_load.save.player.inventory(),
+            ),
+            None => (
+                Transform::from_xyz(0.0, 0.0, 1.0),
+                Jump {
+                    is_jumping: false,
+                    jump_timer: 0.0,
+                    jump_y: 0.0,
+                    jump_cooldown_timer: 0.0,
+                },
+                Inventory::default(),
+            ),
+        };
         commands.spawn((
             SpriteBundle {
-                transform: Transform::from_xyz(0.0, 0.0, 1.0),
+                transform,
                 texture: asset_server.load("sprites/player.png"),
                 ..default()
             },
-            Player {
-                jump: Jump {
-                is_jumping: false,
-                jump_timer: 0.0,
-                jump_y: 0.0,
-                jump_cooldown_timer: 0.0,
-                }
+            Player { jump },
+            inventory,
+            YSort {
+                base_offset: -PLAYER_COLLIDER_HALF_SIZE.y,
+                airborne: false,
             },
         ));
     }
@@ -45,38 +81,82 @@ pub fn despawn_player(
 }
 
 pub fn player_movement(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut player_query: Query<(&mut Transform, &mut Player), With<Player>>,
+    actions: Res<ButtonInput<Action>>,
+    analog_input: Res<AnalogInput>,
+    mut player_query: Query<(&mut Transform, &mut Player, &mut YSort), With<Player>>,
     mut camera_query: Query<&mut Transform, (With<PlayerCamera>, Without<Player>)>,
+    the_world: Res<TheWorld>,
+    weather: Res<Weather>,
+    mut click_move: ResMut<ClickMove>,
+    mut pathfinder: ResMut<Pathfinder>,
     time: Res<Time>,
 ) {
-    if let Ok((mut transform, mut player)) = player_query.get_single_mut() {
+    if let Ok((mut transform, mut player, mut y_sort)) = player_query.get_single_mut() {
         let mut direction = Vec3::ZERO;
 
-        if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
+        if actions.pressed(Action::MoveLeft) {
             direction += Vec3::new(-1.0, 0.0, 0.0);
         }
-        if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
+        if actions.pressed(Action::MoveRight) {
             direction += Vec3::new(1.0, 0.0, 0.0);
         }
-        if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
+        if actions.pressed(Action::MoveUp) {
             direction += Vec3::new(0.0, 1.0, 0.0);
         }
-        if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
+        if actions.pressed(Action::MoveDown) {
             direction += Vec3::new(0.0, -1.0, 0.0);
         }
 
         if direction.length() > 0.0 {
             direction = direction.normalize();
+        } else {
+            // A partly tilted stick walks slower instead of being normalized to full speed
+            direction = analog_input.movement.extend(0.0);
+        }
+
+        let ground = player_ground(&transform, &player);
+        // Any movement input takes over from a click
+        let waypoint = if direction != Vec3::ZERO {
+            if click_move.destination.is_some() {
+                click_move.cancel(&mut pathfinder);
+            }
+            None
+        } else {
+            click_move.next_waypoint(ground)
+        };
+        if let Some(waypoint) = waypoint {
+            direction = (waypoint - ground).normalize_or_zero().extend(0.0);
+        }
+
+        let speed = PLAYER_SPEED as f32 * wet_speed(&the_world, &weather, ground);
+        let mut move_distance = direction * speed * time.delta_seconds();
+        // Stops on the waypoint instead of overshooting it
+        if let Some(waypoint) = waypoint {
+            move_distance = move_distance.clamp_length_max(waypoint.distance(ground));
         }
 
-        let move_distance = direction * PLAYER_SPEED as f32 * time.delta_seconds();
-        transform.translation += move_distance;
+        let previous_ground = ground;
+        let ground = move_with_collision(
+            &the_world,
+            ground,
+            move_distance.truncate(),
+            player.jump.is_jumping,
+        );
+        // Something was built across the route, or the player is caught on a corner
+        if waypoint.is_some() && ground.distance(previous_ground) < move_distance.length() * 0.1 {
+            click_move.cancel(&mut pathfinder);
+        }
+        transform.translation.x = ground.x;
+        if player.jump.is_jumping {
+            player.jump.jump_y = ground.y;
+        } else {
+            transform.translation.y = ground.y;
+        }
 
-        if keyboard_input.just_pressed(KeyCode::Space) && !player.jump.is_jumping && player.jump.jump_cooldown_timer <= 0.0
-            || keyboard_input.pressed(KeyCode::Space) && !player.jump.is_jumping && player.jump.jump_cooldown_timer <= 0.0
+        if actions.pressed(Action::Jump)
+            && !player.jump.is_jumping
+            && player.jump.jump_cooldown_timer <= 0.0
         {
-            transform.translation.z = 2.1;
             player.jump.is_jumping = true;
             transform.scale *= Vec3::splat(JUMP_SCALE);
             player.jump.jump_y = transform.translation.y;
@@ -99,7 +179,6 @@ pub fn player_movement(
             }
 
             if player.jump.jump_timer >= JUMP_DURATION {
-                transform.translation.z = 1.0;
                 player.jump.is_jumping = false;
                 transform.scale /= Vec3::splat(JUMP_SCALE);
                 player.jump.jump_timer = 0.0;
@@ -111,9 +190,245 @@ pub fn player_movement(
             }
         }
 
+        // Landing in the middle of a gap, or spawning on water or a tree, puts the player back on solid ground
+        if !player.jump.is_jumping && !is_area_walkable(&the_world, transform.translation.truncate()) {
+            if let Some(ground) = nearest_walkable(&the_world, transform.translation.truncate()) {
+                transform.translation.x = ground.x;
+                transform.translation.y = ground.y;
+            }
+        }
+
+        y_sort.airborne = player.jump.is_jumping;
+
         if let Ok(mut camera_transform) = camera_query.get_single_mut() {
             camera_transform.translation.x = transform.translation.x;
             camera_transform.translation.y = transform.translation.y;
         }
     }
 }
+
+pub fn harvest_structures(
+    mut commands: Commands,
+    actions: Res<ButtonInput<Action>>,
+    time: Res<Time>,
+    mut the_world: ResMut<TheWorld>,
+    mut harvest_progress: ResMut<HarvestProgress>,
+    mut player_query: Query<(&Transform, &Player, &mut Inventory)>,
+    bar_query: Query<Entity, With<HarvestBar>>,
+    mut fill_query: Query<(&mut Transform, &mut Sprite), (With<HarvestBarFill>, Without<Player>)>,
+) {
+    let Ok((transform, player, mut inventory)) = player_query.get_single_mut() else {
+        return;
+    };
+    let target = if actions.pressed(Action::Interact) && !player.jump.is_jumping {
+        harvest_target(&the_world, transform.translation.truncate())
+            .filter(|(_, harvest)| inventory.can_add(&harvest.drops))
+    } else {
+        None
+    };
+    let Some((anchor, harvest)) = target else {
+        *harvest_progress = HarvestProgress::default();
+        for bar_entity in bar_query.iter() {
+            commands.entity(bar_entity).despawn_recursive();
+        }
+        return;
+    };
+
+    if harvest_progress.target != Some(anchor) {
+        *harvest_progress = HarvestProgress {
+            target: Some(anchor),
+            elapsed: 0.0,
+        };
+        for bar_entity in bar_query.iter() {
+            commands.entity(bar_entity).despawn_recursive();
+        }
+        spawn_harvest_bar(&mut commands, &the_world, anchor);
+    }
+    harvest_progress.elapsed += time.delta_seconds();
+
+    let progress = (harvest_progress.elapsed / harvest.duration.max(f32::EPSILON)).min(1.0);
+    for (mut fill_transform, mut sprite) in fill_query.iter_mut() {
+        let width = HARVEST_BAR_WIDTH * progress;
+        sprite.custom_size = Some(Vec2::new(width, HARVEST_BAR_HEIGHT));
+        fill_transform.translation.x = (width - HARVEST_BAR_WIDTH) / 2.0;
+    }
+
+    if progress >= 1.0 {
+        for (item, count) in &harvest.drops {
+            inventory.add(*item, *count);
+        }
+        the_world.remove_structure(anchor);
+        *harvest_progress = HarvestProgress::default();
+        for bar_entity in bar_query.iter() {
+            commands.entity(bar_entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_harvest_bar(commands: &mut Commands, the_world: &TheWorld, anchor: IVec2) {
+    let footprint = the_world
+        .structure_at(anchor)
+        .map(|(_, structure_type)| structure_type.footprint())
+        .unwrap_or((1, 1));
+    let position = tile_position(anchor);
+    // Centred over the footprint, a little below its top edge so it stays over the sprite
+    let x = position.x + (footprint.0.max(1) as f32 - 1.0) * TILE_SIZE / 2.0;
+    let y = position.y + (footprint.1.max(1) as f32 - 0.5) * TILE_SIZE;
+
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.0, 0.0, 0.0, 0.7),
+                    custom_size: Some(Vec2::new(HARVEST_BAR_WIDTH + 4.0, HARVEST_BAR_HEIGHT + 4.0)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, y, OVERLAY_Z),
+                ..default()
+            },
+            HarvestBar {},
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.95, 0.8, 0.25),
+                        custom_size: Some(Vec2::new(0.0, HARVEST_BAR_HEIGHT)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(-HARVEST_BAR_WIDTH / 2.0, 0.0, 0.1),
+                    ..default()
+                },
+                HarvestBarFill {},
+            ));
+        });
+}
+
+// Left clicks that would not build anything pick where to walk to
+pub fn plan_click_move(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    settings: Res<Settings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    player_query: Query<(&Transform, &Player)>,
+    the_world: Res<TheWorld>,
+    palette: Res<BuildPalette>,
+    time: Res<Time>,
+    mut pathfinder: ResMut<Pathfinder>,
+    mut click_move: ResMut<ClickMove>,
+) {
+    if let Some((_, remaining)) = click_move.refused.as_mut() {
+        *remaining -= time.delta_seconds();
+        if *remaining <= 0.0 {
+            click_move.refused = None;
+        }
+    }
+    if !settings.click_to_move {
+        if click_move.destination.is_some() {
+            click_move.cancel(&mut pathfinder);
+        }
+        return;
+    }
+
+    if let Some(request) = click_move.request {
+        match pathfinder.poll(request) {
+            PathStatus::Pending => {}
+            PathStatus::Found(path) => {
+                click_move.request = None;
+                // The first tile is the one the player stands on
+                click_move.route = path.into_iter().skip(1).collect();
+            }
+            PathStatus::NoPath => {
+                click_move.request = None;
+                if let Some(destination) = click_move.destination {
+                    click_move.refuse(destination, &mut pathfinder);
+                }
+            }
+        }
+    }
+
+    // Left click builds while a brush is in hand
+    if !mouse_input.just_pressed(MouseButton::Left) || palette.brush().is_some() {
+        return;
+    }
+    let cursor = window_query
+        .get_single()
+        .ok()
+        .zip(camera_query.get_single().ok())
+        .and_then(|(window, (camera, camera_transform))| cursor_to_world(window, camera, camera_transform));
+    let (Some(cursor), Ok((transform, player))) = (cursor, player_query.get_single()) else {
+        return;
+    };
+    let destination = world_to_tile(cursor);
+    if !the_world.is_walkable(destination) {
+        click_move.refuse(destination, &mut pathfinder);
+        return;
+    }
+    click_move.cancel(&mut pathfinder);
+    click_move.refused = None;
+    click_move.destination = Some(destination);
+    click_move.request = Some(pathfinder.request(world_to_tile(player_ground(transform, player)), destination));
+}
+
+// Redrawn only when the route changes, the destination is marked before its path is found
+pub fn show_click_move(
+    mut commands: Commands,
+    click_move: Res<ClickMove>,
+    mut shown: Local<(Option<IVec2>, Vec<IVec2>, Option<IVec2>)>,
+    mut refused_query: Query<&mut Sprite, With<DestinationMarker>>,
+    marker_query: Query<Entity, Or<(With<DestinationMarker>, With<RouteMarker>)>>,
+) {
+    let refused = click_move.refused.map(|(tile_pos, _)| tile_pos);
+    if shown.0 != click_move.destination || shown.1 != click_move.route || shown.2 != refused {
+        *shown = (click_move.destination, click_move.route.clone(), refused);
+        for marker_entity in marker_query.iter() {
+            commands.entity(marker_entity).despawn();
+        }
+        if let Some(destination) = click_move.destination {
+            spawn_marker(&mut commands, destination, DESTINATION_COLOR, DESTINATION_MARKER_SIZE, DestinationMarker {});
+        }
+        for tile_pos in click_move.route.iter().rev().skip(1) {
+            spawn_marker(&mut commands, *tile_pos, ROUTE_COLOR, ROUTE_MARKER_SIZE, RouteMarker {});
+        }
+        if let Some(refused) = refused {
+            spawn_marker(&mut commands, refused, REFUSED_COLOR, DESTINATION_MARKER_SIZE, DestinationMarker {});
+        }
+        return;
+    }
+    // A refused marker fades away
+    if let Some((_, remaining)) = click_move.refused {
+        for mut sprite in refused_query.iter_mut() {
+            sprite.color = REFUSED_COLOR.with_a(REFUSED_COLOR.a() * remaining / REFUSED_MARKER_TIME);
+        }
+    }
+}
+
+pub fn reset_click_move(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    mut click_move: ResMut<ClickMove>,
+    marker_query: Query<Entity, Or<(With<DestinationMarker>, With<RouteMarker>)>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        *click_move = ClickMove::default();
+        for marker_entity in marker_query.iter() {
+            commands.entity(marker_entity).despawn();
+        }
+    }
+}
+
+fn spawn_marker(commands: &mut Commands, tile_pos: IVec2, color: Color, size: f32, marker: impl Component) {
+    let position = tile_position(tile_pos);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(size)),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, OVERLAY_Z),
+            ..default()
+        },
+        marker,
+    ));
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::weather::resources::*;
+use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::systems::*;
+use crate::world::utils::*;
+
+pub const PLAYER_COLLIDER_HALF_SIZE: Vec2 = Vec2::new(12.0, 12.0);
+// Keeps each collision step under half a tile so fast frames cannot tunnel through a tree
+const MAX_COLLISION_STEP: f32 = TILE_SIZE / 2.0;
+
+// While jumping the sprite follows the arc, so the ground position is below it
+pub fn player_ground(transform: &Transform, player: &Player) -> Vec2 {
+    if player.jump.is_jumping {
+        Vec2::new(transform.translation.x, player.jump.jump_y)
+    } else {
+        transform.translation.truncate()
+    }
+}
+
+// Tiles the player collider overlaps
+pub fn player_tiles(ground: Vec2) -> impl Iterator<Item = IVec2> {
+    let min = world_to_tile(ground - PLAYER_COLLIDER_HALF_SIZE);
+    let max = world_to_tile(ground + PLAYER_COLLIDER_HALF_SIZE);
+    (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+}
+
+// Rain turns some ground to mud, taken from the tile under the centre of the player
+pub fn wet_speed(the_world: &TheWorld, weather: &Weather, ground: Vec2) -> f32 {
+    let tile_speed = the_world
+        .get_tile(world_to_tile(ground))
+        .map(|tile| tile.tile_type.wet_speed())
+        .unwrap_or(1.0);
+    1.0 + (tile_speed - 1.0) * weather.wetness()
+}
+
+pub fn is_area_walkable(the_world: &TheWorld, ground: Vec2) -> bool {
+    player_tiles(ground).all(|tile_pos| the_world.is_walkable(tile_pos))
+}
+
+fn is_area_loaded(the_world: &TheWorld, ground: Vec2) -> bool {
+    player_tiles(ground).all(|tile_pos| the_world.get_tile(tile_pos).is_some())
+}
+
+// While airborne the player may pass over blocked tiles as long as each one
+// has walkable ground right behind it, which limits jumps to single-tile gaps
+fn is_area_jumpable(the_world: &TheWorld, ground: Vec2, direction: IVec2) -> bool {
+    player_tiles(ground).all(|tile_pos| {
+        the_world.is_walkable(tile_pos)
+            || the_world.get_tile(tile_pos).is_some() && the_world.is_walkable(tile_pos + direction)
+    })
+}
+
+fn can_occupy(the_world: &TheWorld, ground: Vec2, direction: IVec2, airborne: bool) -> bool {
+    if airborne {
+        is_area_jumpable(the_world, ground, direction)
+    } else {
+        is_area_walkable(the_world, ground)
+    }
+}
+
+pub fn move_with_collision(the_world: &TheWorld, ground: Vec2, movement: Vec2, airborne: bool) -> Vec2 {
+    let steps = (movement.abs().max_element() / MAX_COLLISION_STEP).ceil().max(1.0) as i32;
+    let step = movement / steps as f32;
+    let mut ground = ground;
+
+    // Each axis is resolved on its own so the player slides along obstacles
+    for _ in 0..steps {
+        let next_x = ground + Vec2::new(step.x, 0.0);
+        if step.x != 0.0 && can_occupy(the_world, next_x, IVec2::new(step.x.signum() as i32, 0), airborne) {
+            ground = next_x;
+        }
+        let next_y = ground + Vec2::new(0.0, step.y);
+        if step.y != 0.0 && can_occupy(the_world, next_y, IVec2::new(0, step.y.signum() as i32), airborne) {
+            ground = next_y;
+        }
+    }
+    ground
+}
+
+// Finds the closest spot the player can stand on, used after landing in a gap
+// or when the player is spawned on top of water or a tree
+pub fn nearest_walkable(the_world: &TheWorld, ground: Vec2) -> Option<Vec2> {
+    if !is_area_loaded(the_world, ground) {
+        return None;
+    }
+    let origin = world_to_tile(ground);
+    for radius in 1..=CHUNK_SIZE {
+        let mut candidates: Vec<Vec2> = vec![];
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x.abs() != radius && y.abs() != radius {
+                    continue;
+                }
+                let position = tile_position(origin + IVec2::new(x, y));
+                let candidate = Vec2::new(position.x, position.y);
+                if is_area_walkable(the_world, candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+        if let Some(closest) = candidates
+            .into_iter()
+            .min_by(|a, b| a.distance_squared(ground).total_cmp(&b.distance_squared(ground)))
+        {
+            return Some(closest);
+        }
+    }
+    None
+}
+
+// The anchor of the closest harvestable structure touching the tiles the player stands on
+pub fn harvest_target(the_world: &TheWorld, ground: Vec2) -> Option<(IVec2, Harvest)> {
+    let min = world_to_tile(ground - PLAYER_COLLIDER_HALF_SIZE) - IVec2::ONE;
+    let max = world_to_tile(ground + PLAYER_COLLIDER_HALF_SIZE) + IVec2::ONE;
+    let mut closest: Option<(f32, IVec2, Harvest)> = None;
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let tile_pos = IVec2::new(x, y);
+            let Some((anchor, structure_type)) = the_world.structure_at(tile_pos) else {
+                continue;
+            };
+            let Some(harvest) = structure_type.definition().harvest else {
+                continue;
+            };
+            let position = tile_position(tile_pos);
+            let distance = Vec2::new(position.x, position.y).distance_squared(ground);
+            if closest.as_ref().map_or(true, |(closest_distance, _, _)| distance < *closest_distance) {
+                closest = Some((distance, anchor, harvest));
+            }
+        }
+    }
+    closest.map(|(_, anchor, harvest)| (anchor, harvest))
+}
@@ -1,4 +1,8 @@
 use bevy::prelude::*;
+use rand::prelude::*;
+use std::path::PathBuf;
+
+use crate::world::resources::GeneratorKind;
 
 #[derive(Event)]
 pub struct GameStart {}
@@ -6,10 +10,66 @@ pub struct GameStart {}
 #[derive(Event)]
 pub struct GameOver {}
 
+// Tiles edited this frame, sent once their chunks have been redrawn. Newly generated
+// chunks send their border, which searches treated as blocked until then.
+#[derive(Event)]
+pub struct TilesChanged {
+    pub tiles: Vec<IVec2>,
+}
+
+#[derive(Event)]
+pub struct SaveGame {
+    pub slot: usize,
+}
+
+#[derive(Event)]
+pub struct LoadGame {
+    pub slot: usize,
+}
+
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
 pub enum GameState {
     #[default]
     Menu,
     Paused,
     Game,
-}
\ No newline at end of file
+    Editor,
+}
+
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum CraftingMenuState {
+    #[default]
+    Closed,
+    Open,
+}
+
+// Focus navigation activates buttons by pressing their Interaction, so every
+// button handler runs after it to see the press in the same frame
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MenuNavigation;
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldSeed {
+    pub seed: u64,
+}
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        WorldSeed {
+            seed: rand::thread_rng().gen(),
+        }
+    }
+}
+
+// A hand-made map, loaded by new games instead of generating a world from scratch
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Default)]
+pub struct WorldMap {
+    pub path: Option<PathBuf>,
+    // Picked with --map or from the main menu, otherwise new games generate from the seed
+    pub selected: bool,
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WorldGenerator {
+    pub kind: GeneratorKind,
+}
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+pub mod resources;
+mod systems;
+pub mod utils;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Game), restore_camera_zoom)
+            .add_systems(
+                Update,
+                (
+                    save_game,
+                    load_game.run_if(in_state(GameState::Menu)),
+                    clear_pending_load.run_if(resource_exists::<PendingLoad>),
+                ),
+            );
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::*;
+use crate::daylight::resources::*;
+use crate::inventory::components::*;
+use crate::weather::resources::*;
+use crate::world::components::*;
+use crate::world::resources::*;
+use crate::world::tiles::*;
+
+pub const SAVE_VERSION: u32 = 2;
+// Version 1 saves stored palms as `Tree(Single)` and `Tree(Double)`, which still load
+pub const OLDEST_SAVE_VERSION: u32 = 1;
+pub const SAVE_SLOTS: usize = 3;
+
+#[derive(Deserialize)]
+pub struct SaveHeader {
+    pub version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SaveFile {
+    pub version: u32,
+    pub world: SavedWorld,
+    pub player: SavedPlayer,
+    pub camera_zoom: f32,
+    #[serde(default)]
+    pub clock: WorldClock,
+    // Saves without weather start over from the first spell
+    #[serde(default)]
+    pub weather: Option<SavedWeather>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedWorld {
+    pub seed: u64,
+    // Saves from before generators were selectable all used the chunk vote
+    #[serde(default)]
+    pub generator: GeneratorKind,
+    pub chunks: Vec<SavedChunk>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedChunk {
+    pub pos: (i32, i32),
+    pub biome: TileType,
+    pub tiles: Vec<Vec<Tile>>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedPlayer {
+    pub translation: [f32; 3],
+    pub scale: [f32; 3],
+    pub jump: Jump,
+    #[serde(default)]
+    pub inventory: Inventory,
+}
+
+impl From<&TheWorld> for SavedWorld {
+    fn from(the_world: &TheWorld) -> Self {
+        let mut chunks: Vec<SavedChunk> = the_world
+            .chunks
+            .iter()
+            .map(|(chunk_pos, chunk)| SavedChunk {
+                pos: (chunk_pos.x, chunk_pos.y),
+                biome: chunk.biome,
+                tiles: chunk.tiles.clone(),
+            })
+            .collect();
+        // Stable order so two saves of the same world diff cleanly
+        chunks.sort_by_key(|chunk| chunk.pos);
+
+        SavedWorld {
+            seed: the_world.seed,
+            generator: the_world.generator,
+            chunks,
+        }
+    }
+}
+
+impl SavedWorld {
+    pub fn restore(&self, the_world: &mut TheWorld) {
+        the_world.reset(self.seed, self.generator);
+        for saved_chunk in self.chunks.iter() {
+            the_world.chunks.insert(
+                IVec2::new(saved_chunk.pos.0, saved_chunk.pos.1),
+                Chunk {
+                    biome: saved_chunk.biome,
+                    tiles: saved_chunk.tiles.clone(),
+                },
+            );
+        }
+    }
+}
+
+impl SavedPlayer {
+    pub fn new(transform: &Transform, player: &Player, inventory: &Inventory) -> Self {
+        SavedPlayer {
+            translation: transform.translation.to_array(),
+            scale: transform.scale.to_array(),
+            jump: player.jump.clone(),
+            inventory: inventory.clone(),
+        }
+    }
+
+    // Saves made with fewer slots get empty ones added
+    pub fn inventory(&self) -> Inventory {
+        let mut inventory = self.inventory.clone();
+        if inventory.slots.len() < INVENTORY_SLOTS {
+            inventory.slots.resize(INVENTORY_SLOTS, None);
+        }
+        inventory
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.translation))
+            .with_scale(Vec3::from_array(self.scale))
+    }
+}
+
+#[derive(Resource)]
+pub struct PendingLoad {
+    pub save: SaveFile,
+}
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::daylight::resources::*;
+use crate::inventory::components::*;
+use crate::resources::*;
+use crate::save::resources::*;
+use crate::save::utils::*;
+use crate::weather::resources::*;
+use crate::world::resources::*;
+
+pub fn save_game(
+    mut reader: EventReader<SaveGame>,
+    the_world: Res<TheWorld>,
+    clock: Res<WorldClock>,
+    weather: Res<Weather>,
+    player_query: Query<(&Transform, &Player, &Inventory)>,
+    camera_query: Query<&OrthographicProjection, With<PlayerCamera>>,
+) {
+    for save_game in reader.read() {
+        if let Ok((player_transform, player, inventory)) = player_query.get_single() {
+            let save = SaveFile {
+                version: SAVE_VERSION,
+                world: SavedWorld::from(&*the_world),
+                player: SavedPlayer::new(player_transform, player, inventory),
+                camera_zoom: camera_query
+                    .get_single()
+                    .map(|projection| projection.scale)
+                    .unwrap_or(1.0),
+                clock: *clock,
+                weather: Some(weather.saved()),
+            };
+            match write_save(save_game.slot, &save) {
+                Ok(()) => info!("Saved game to {}", slot_path(save_game.slot).display()),
+                Err(err) => error!("Failed to save slot {}: {}", save_game.slot + 1, err),
+            }
+        }
+    }
+}
+
+pub fn load_game(
+    mut commands: Commands,
+    mut reader: EventReader<LoadGame>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut game_start_event_writer: EventWriter<GameStart>,
+    mut world_seed: ResMut<WorldSeed>,
+) {
+    if let Some(load_game) = reader.read().last() {
+        match read_save(load_game.slot) {
+            Ok(save) => {
+                world_seed.seed = save.world.seed;
+                commands.insert_resource(PendingLoad { save });
+                game_state.set(GameState::Game);
+                game_start_event_writer.send(GameStart {});
+            }
+            Err(err) => error!("Failed to load slot {}: {}", load_game.slot + 1, err),
+        }
+    }
+}
+
+pub fn restore_camera_zoom(
+    pending_load: Option<Res<PendingLoad>>,
+    mut camera_query: Query<&mut OrthographicProjection, With<PlayerCamera>>,
+) {
+    if let Some(pending_load) = pending_load {
+        for mut projection in camera_query.iter_mut() {
+            projection.scale = pending_load.save.camera_zoom;
+        }
+    }
+}
+
+pub fn clear_pending_load(mut commands: Commands) {
+    commands.remove_resource::<PendingLoad>();
+}
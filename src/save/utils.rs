@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::resources::*;
+use crate::save::resources::*;
+
+pub fn slot_path(slot: usize) -> PathBuf {
+    PathBuf::from("saves").join(format!("slot_{}.ron", slot + 1))
+}
+
+pub fn slot_exists(slot: usize) -> bool {
+    slot_path(slot).is_file()
+}
+
+pub fn latest_slot() -> Option<usize> {
+    (0..SAVE_SLOTS)
+        .filter_map(|slot| {
+            let modified: SystemTime = fs::metadata(slot_path(slot)).ok()?.modified().ok()?;
+            Some((slot, modified))
+        })
+        .max_by_key(|(_, modified)| *modified)
+        .map(|(slot, _)| slot)
+}
+
+// Where the editor exports to when no map was picked with `--map`
+pub fn default_map_path() -> PathBuf {
+    PathBuf::from("maps/editor.ron")
+}
+
+pub fn editor_map_path(world_map: &WorldMap) -> PathBuf {
+    world_map.path.clone().unwrap_or_else(default_map_path)
+}
+
+pub fn game_map_path(world_map: &WorldMap) -> Option<PathBuf> {
+    world_map.selected.then(|| editor_map_path(world_map))
+}
+
+// Maps are a saved world on its own, chunks outside it are generated from its seed
+pub fn write_map(path: &Path, world: &SavedWorld) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    // Deeper levels stay on one line so every tile row is a single diffable line
+    let pretty = ron::ser::PrettyConfig::default().depth_limit(4);
+    let contents = ron::ser::to_string_pretty(world, pretty).map_err(|err| err.to_string())?;
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+pub fn read_map(path: &Path) -> Result<SavedWorld, String> {
+    fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+pub fn write_save(slot: usize, save: &SaveFile) -> Result<(), String> {
+    let path = slot_path(slot);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let contents = ron::to_string(save).map_err(|err| err.to_string())?;
+    fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+pub fn read_save(slot: usize) -> Result<SaveFile, String> {
+    let contents = fs::read_to_string(slot_path(slot)).map_err(|err| err.to_string())?;
+
+    // Check the version before anything else so older or newer files fail
+    // with a readable message instead of a field mismatch
+    let header: SaveHeader = ron::from_str(&contents).map_err(|err| err.to_string())?;
+    if !(OLDEST_SAVE_VERSION..=SAVE_VERSION).contains(&header.version) {
+        return Err(format!(
+            "unsupported save version {} (expected {} to {})",
+            header.version, OLDEST_SAVE_VERSION, SAVE_VERSION
+        ));
+    }
+    ron::from_str(&contents).map_err(|err| err.to_string())
+}
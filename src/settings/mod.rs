@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+pub mod resources;
+mod systems;
+pub mod utils;
+
+use resources::*;
+use systems::*;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_window_settings.run_if(resource_changed::<Settings>),
+                persist_settings
+                    .run_if(resource_changed::<Settings>.and_then(not(resource_added::<Settings>))),
+            ),
+        );
+    }
+}
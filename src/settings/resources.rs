@@ -0,0 +1,126 @@
+use bevy::{
+    prelude::*,
+    window::{PresentMode, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::controls::resources::*;
+
+pub const MIN_DAY_LENGTH: f32 = 10.0;
+// Zoom limits stay between these, the smallest also being how far Min Zoom steps
+pub const MIN_ZOOM_STEP: f32 = 0.05;
+pub const ZOOM_LIMIT: f32 = 20.0;
+
+pub const RESOLUTIONS: [(u32, u32); 5] = [
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowModeSetting {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen,
+            // Sized so the chosen resolution is honoured instead of the monitor's largest mode
+            WindowModeSetting::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+
+    pub fn cycle(&self, forward: bool) -> WindowModeSetting {
+        match (self, forward) {
+            (WindowModeSetting::Windowed, true) => WindowModeSetting::Borderless,
+            (WindowModeSetting::Borderless, true) => WindowModeSetting::Fullscreen,
+            (WindowModeSetting::Fullscreen, true) => WindowModeSetting::Windowed,
+            (WindowModeSetting::Windowed, false) => WindowModeSetting::Fullscreen,
+            (WindowModeSetting::Borderless, false) => WindowModeSetting::Windowed,
+            (WindowModeSetting::Fullscreen, false) => WindowModeSetting::Borderless,
+        }
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub resolution: (u32, u32),
+    pub window_mode: WindowModeSetting,
+    pub vsync: bool,
+    pub show_fps: bool,
+    pub show_coordinates: bool,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    pub key_bindings: KeyBindings,
+    pub gamepad_deadzone: f32,
+    // Clicking the ground walks the player there along a path
+    pub click_to_move: bool,
+    // Real seconds in a full day and night
+    pub day_length: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            resolution: (1920, 1080),
+            window_mode: WindowModeSetting::Fullscreen,
+            vsync: true,
+            show_fps: true,
+            show_coordinates: true,
+            min_zoom: 0.1,
+            max_zoom: 5.0,
+            key_bindings: KeyBindings::default(),
+            gamepad_deadzone: 0.2,
+            click_to_move: false,
+            day_length: 600.0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    // Keeps a hand-edited day length from spinning the clock or dividing by zero
+    pub fn day_length(&self) -> f32 {
+        self.day_length.max(MIN_DAY_LENGTH)
+    }
+
+    // Keeps hand-edited zoom limits from making the camera's clamp panic
+    pub fn fix_zoom_limits(&mut self) {
+        let defaults = Settings::default();
+        if !self.min_zoom.is_finite() {
+            self.min_zoom = defaults.min_zoom;
+        }
+        if !self.max_zoom.is_finite() {
+            self.max_zoom = defaults.max_zoom;
+        }
+        self.min_zoom = self.min_zoom.clamp(MIN_ZOOM_STEP, ZOOM_LIMIT);
+        self.max_zoom = self.max_zoom.clamp(self.min_zoom, ZOOM_LIMIT);
+    }
+
+    pub fn window_resolution(&self) -> WindowResolution {
+        (self.resolution.0 as f32, self.resolution.1 as f32).into()
+    }
+
+    pub fn cycle_resolution(&self, forward: bool) -> (u32, u32) {
+        let current = RESOLUTIONS.iter().position(|resolution| *resolution == self.resolution);
+        match (current, forward) {
+            (Some(index), true) => RESOLUTIONS[(index + 1) % RESOLUTIONS.len()],
+            (Some(index), false) => RESOLUTIONS[(index + RESOLUTIONS.len() - 1) % RESOLUTIONS.len()],
+            (None, _) => RESOLUTIONS[0],
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::settings::resources::*;
+use crate::settings::utils::*;
+
+pub fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if let Ok(mut window) = window_query.get_single_mut() {
+        let (width, height) = settings.resolution;
+        if window.resolution.width() != width as f32 || window.resolution.height() != height as f32 {
+            window.resolution.set(width as f32, height as f32);
+        }
+        if window.mode != settings.window_mode.window_mode() {
+            window.mode = settings.window_mode.window_mode();
+        }
+        if window.present_mode != settings.present_mode() {
+            window.present_mode = settings.present_mode();
+        }
+    }
+}
+
+pub fn persist_settings(settings: Res<Settings>) {
+    if let Err(err) = save_settings(&settings) {
+        error!("Failed to save {}: {}", settings_path().display(), err);
+    }
+}
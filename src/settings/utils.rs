@@ -0,0 +1,49 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::settings::resources::*;
+
+pub fn settings_path() -> PathBuf {
+    PathBuf::from("settings.ron")
+}
+
+// Runs before the app (and its logger) exists, so problems go straight to stderr
+pub fn load_settings() -> Settings {
+    match fs::read_to_string(settings_path()) {
+        Ok(contents) => parse_settings(&contents).unwrap_or_else(|err| {
+            eprintln!("Ignoring invalid {}: {}", settings_path().display(), err);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+pub fn parse_settings(contents: &str) -> Result<Settings, ron::error::SpannedError> {
+    let mut settings: Settings = ron::from_str(contents)?;
+    settings.fix_zoom_limits();
+    Ok(settings)
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    let contents = ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())?;
+    fs::write(settings_path(), contents).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_zoom_limits_are_fixed_on_load() {
+        let inverted = parse_settings("(min_zoom: 4.0, max_zoom: 1.0)").unwrap();
+        assert_eq!((inverted.min_zoom, inverted.max_zoom), (4.0, 4.0));
+
+        let defaults = Settings::default();
+        let missing = parse_settings("(min_zoom: NaN, max_zoom: inf)").unwrap();
+        assert_eq!((missing.min_zoom, missing.max_zoom), (defaults.min_zoom, defaults.max_zoom));
+
+        let tiny = parse_settings("(min_zoom: -1.0, max_zoom: 0.01)").unwrap();
+        assert_eq!((tiny.min_zoom, tiny.max_zoom), (MIN_ZOOM_STEP, MIN_ZOOM_STEP));
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+// Screen space layer the rain, fog and lightning are drawn on, under the HUD
+#[derive(Component)]
+pub struct WeatherOverlay {}
+
+#[derive(Component)]
+pub struct FogLayer {}
+
+#[derive(Component)]
+pub struct LightningFlash {
+    // Seconds left until the flash has faded
+    pub remaining: f32,
+}
+
+// Positions are fractions of the screen so drops survive resizing
+#[derive(Component)]
+pub struct RainDrop {
+    pub x: f32,
+    pub y: f32,
+    pub speed: f32,
+    // Drops past the current rain amount stay hidden
+    pub threshold: f32,
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+mod components;
+pub mod resources;
+mod systems;
+
+use crate::resources::*;
+use resources::*;
+use systems::*;
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .add_systems(OnEnter(GameState::Game), (reset_weather, spawn_weather_overlay))
+            .add_systems(OnEnter(GameState::Menu), despawn_weather_overlay)
+            .add_systems(
+                Update,
+                (
+                    advance_weather,
+                    (animate_rain, show_fog, flash_lightning, sway_structures),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Game)),
+            );
+    }
+}
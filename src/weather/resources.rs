@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Seconds a spell of weather lasts, picked per spell
+const MIN_SPELL_LENGTH: f32 = 90.0;
+const MAX_SPELL_LENGTH: f32 = 240.0;
+// Seconds one kind of weather takes to give way to the next
+const TRANSITION_LENGTH: f32 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Storm,
+    Fog,
+}
+
+impl WeatherKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "Clear",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Storm => "Storm",
+            WeatherKind::Fog => "Fog",
+        }
+    }
+
+    // Relative chances of the next spell. Storms only build out of rain.
+    fn next_weights(&self) -> [(WeatherKind, f32); 4] {
+        match self {
+            WeatherKind::Clear => [
+                (WeatherKind::Clear, 0.5),
+                (WeatherKind::Rain, 0.3),
+                (WeatherKind::Storm, 0.0),
+                (WeatherKind::Fog, 0.2),
+            ],
+            WeatherKind::Rain => [
+                (WeatherKind::Clear, 0.45),
+                (WeatherKind::Rain, 0.2),
+                (WeatherKind::Storm, 0.25),
+                (WeatherKind::Fog, 0.1),
+            ],
+            WeatherKind::Storm => [
+                (WeatherKind::Clear, 0.2),
+                (WeatherKind::Rain, 0.7),
+                (WeatherKind::Storm, 0.1),
+                (WeatherKind::Fog, 0.0),
+            ],
+            WeatherKind::Fog => [
+                (WeatherKind::Clear, 0.7),
+                (WeatherKind::Rain, 0.2),
+                (WeatherKind::Storm, 0.0),
+                (WeatherKind::Fog, 0.1),
+            ],
+        }
+    }
+
+    fn rain(&self) -> f32 {
+        match self {
+            WeatherKind::Rain => 0.5,
+            WeatherKind::Storm => 1.0,
+            WeatherKind::Clear | WeatherKind::Fog => 0.0,
+        }
+    }
+
+    fn wind(&self) -> f32 {
+        match self {
+            WeatherKind::Clear => 0.15,
+            WeatherKind::Rain => 0.4,
+            WeatherKind::Storm => 1.0,
+            WeatherKind::Fog => 0.0,
+        }
+    }
+
+    fn fog(&self) -> f32 {
+        match self {
+            WeatherKind::Fog => 1.0,
+            WeatherKind::Rain => 0.15,
+            WeatherKind::Storm => 0.3,
+            WeatherKind::Clear => 0.0,
+        }
+    }
+}
+
+// Spells follow each other as a Markov chain rolled from the world seed and the
+// spell number alone, so a seed always brings the same weather at the same time
+#[derive(Resource, Debug, Clone, PartialEq, Default)]
+pub struct Weather {
+    seed: u64,
+    pub spell: u64,
+    pub kind: WeatherKind,
+    pub previous: WeatherKind,
+    // Seconds into the current spell and its total length
+    pub elapsed: f32,
+    pub length: f32,
+}
+
+impl Weather {
+    pub fn new(seed: u64) -> Self {
+        Weather {
+            seed,
+            spell: 0,
+            kind: WeatherKind::Clear,
+            previous: WeatherKind::Clear,
+            elapsed: TRANSITION_LENGTH,
+            length: spell_length(seed, 0),
+        }
+    }
+
+    // Picks the spell back up where a save left it
+    pub fn restore(seed: u64, saved: &SavedWeather) -> Self {
+        let length = spell_length(seed, saved.spell);
+        Weather {
+            seed,
+            spell: saved.spell,
+            kind: saved.kind,
+            previous: saved.previous,
+            elapsed: saved.elapsed.clamp(0.0, length),
+            length,
+        }
+    }
+
+    pub fn saved(&self) -> SavedWeather {
+        SavedWeather {
+            spell: self.spell,
+            kind: self.kind,
+            previous: self.previous,
+            elapsed: self.elapsed,
+        }
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        self.elapsed += seconds;
+        while self.elapsed >= self.length {
+            self.elapsed -= self.length;
+            self.spell += 1;
+            self.previous = self.kind;
+            self.kind = next_kind(self.seed, self.spell, self.kind);
+            self.length = spell_length(self.seed, self.spell);
+        }
+    }
+
+    // How far the current spell has taken over from the previous one
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / TRANSITION_LENGTH).clamp(0.0, 1.0)
+    }
+
+    fn blend(&self, amount: impl Fn(&WeatherKind) -> f32) -> f32 {
+        let from = amount(&self.previous);
+        from + (amount(&self.kind) - from) * self.progress()
+    }
+
+    pub fn rain(&self) -> f32 {
+        self.blend(WeatherKind::rain)
+    }
+
+    pub fn wind(&self) -> f32 {
+        self.blend(WeatherKind::wind)
+    }
+
+    pub fn fog(&self) -> f32 {
+        self.blend(WeatherKind::fog)
+    }
+
+    // Ground soaks through in any rain, a drizzle counts as much as a storm
+    pub fn wetness(&self) -> f32 {
+        self.blend(|kind| if kind.rain() > 0.0 { 1.0 } else { 0.0 })
+    }
+}
+
+// The spell lengths follow from the world seed, so only the position in the chain is saved
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SavedWeather {
+    pub spell: u64,
+    pub kind: WeatherKind,
+    pub previous: WeatherKind,
+    pub elapsed: f32,
+}
+
+fn spell_rng(seed: u64, spell: u64) -> StdRng {
+    StdRng::seed_from_u64(seed ^ spell.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0x5745_4154_4845_5200)
+}
+
+fn spell_length(seed: u64, spell: u64) -> f32 {
+    let mut rng = spell_rng(seed, spell);
+    rng.gen_range(MIN_SPELL_LENGTH..MAX_SPELL_LENGTH)
+}
+
+fn next_kind(seed: u64, spell: u64, current: WeatherKind) -> WeatherKind {
+    let mut rng = spell_rng(seed, spell);
+    // The first roll went to the spell length
+    rng.gen::<f32>();
+    let weights = current.next_weights();
+    let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.gen::<f32>() * total;
+    for (kind, weight) in weights {
+        if roll < weight {
+            return kind;
+        }
+        roll -= weight;
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forecast(seed: u64, spells: usize) -> Vec<WeatherKind> {
+        let mut weather = Weather::new(seed);
+        let mut kinds = vec![weather.kind];
+        while kinds.len() < spells {
+            let spell = weather.spell;
+            weather.advance(1.0);
+            if weather.spell != spell {
+                kinds.push(weather.kind);
+            }
+        }
+        kinds
+    }
+
+    #[test]
+    fn same_seed_brings_the_same_weather() {
+        assert_eq!(forecast(42, 50), forecast(42, 50));
+    }
+
+    #[test]
+    fn different_seeds_bring_different_weather() {
+        assert_ne!(forecast(1, 50), forecast(2, 50));
+    }
+
+    #[test]
+    fn frame_rate_does_not_change_the_weather() {
+        let mut slow = Weather::new(7);
+        let mut fast = Weather::new(7);
+        for _ in 0..600 {
+            slow.advance(5.0);
+        }
+        for _ in 0..30_000 {
+            fast.advance(0.1);
+        }
+        assert_eq!(slow.spell, fast.spell);
+        assert_eq!(slow.kind, fast.kind);
+    }
+
+    #[test]
+    fn storms_only_follow_rain() {
+        let kinds = forecast(1234, 500);
+        for pair in kinds.windows(2) {
+            if pair[1] == WeatherKind::Storm {
+                assert!(matches!(pair[0], WeatherKind::Rain | WeatherKind::Storm), "{:?}", pair);
+            }
+        }
+        assert!(kinds.contains(&WeatherKind::Storm));
+        assert!(kinds.contains(&WeatherKind::Fog));
+    }
+
+    #[test]
+    fn restored_weather_carries_on_the_same_forecast() {
+        let mut weather = Weather::new(7);
+        weather.advance(1000.0);
+        let mut restored = Weather::restore(7, &weather.saved());
+        assert_eq!(restored, weather);
+        weather.advance(2000.0);
+        restored.advance(2000.0);
+        assert_eq!(restored, weather);
+    }
+
+    #[test]
+    fn effects_fade_between_spells() {
+        let mut weather = Weather::new(0);
+        weather.previous = WeatherKind::Clear;
+        weather.kind = WeatherKind::Storm;
+        weather.elapsed = 0.0;
+        assert_eq!(weather.rain(), 0.0);
+        weather.elapsed = TRANSITION_LENGTH / 2.0;
+        assert!((weather.rain() - 0.5).abs() < 1e-6);
+        weather.elapsed = TRANSITION_LENGTH;
+        assert_eq!(weather.rain(), 1.0);
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::resources::*;
+use crate::save::resources::*;
+use crate::weather::components::*;
+use crate::weather::resources::*;
+use crate::world::components::*;
+
+const RAIN_DROPS: usize = 160;
+const RAIN_COLOR: Color = Color::rgba(0.7, 0.8, 1.0, 0.45);
+const FOG_COLOR: Color = Color::rgb(0.78, 0.8, 0.82);
+// Opacity of the fog layer in thick fog
+const MAX_FOG: f32 = 0.7;
+// Chance of lightning each second at the height of a storm
+const LIGHTNING_CHANCE: f32 = 0.08;
+const LIGHTNING_LENGTH: f32 = 0.35;
+// Swaying cycles per second in a full gale
+const SWAY_SPEED: f32 = 1.5;
+
+pub fn reset_weather(
+    mut reader: EventReader<GameStart>,
+    mut weather: ResMut<Weather>,
+    world_seed: Res<WorldSeed>,
+    pending_load: Option<Res<PendingLoad>>,
+) {
+    if let Some(_game_start) = reader.read().last() {
+        *weather = match pending_load.as_ref().and_then(|pending_load| pending_load.save.weather) {
+            Some(saved) => Weather::restore(world_seed.seed, &saved),
+            None => Weather::new(world_seed.seed),
+        };
+    }
+}
+
+pub fn advance_weather(mut weather: ResMut<Weather>, time: Res<Time>) {
+    let kind = weather.kind;
+    weather.advance(time.delta_seconds());
+    if weather.kind != kind {
+        info!("Weather turned to {}", weather.kind.label());
+    }
+}
+
+pub fn spawn_weather_overlay(mut commands: Commands, mut reader: EventReader<GameStart>) {
+    if let Some(_game_start) = reader.read().last() {
+        let mut rng = thread_rng();
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    z_index: ZIndex::Global(-1),
+                    ..default()
+                },
+                WeatherOverlay {},
+            ))
+            .with_children(|parent| {
+                for _ in 0..RAIN_DROPS {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                width: Val::Px(2.0),
+                                height: Val::Px(14.0),
+                                ..default()
+                            },
+                            background_color: RAIN_COLOR.into(),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        RainDrop {
+                            x: rng.gen(),
+                            y: rng.gen(),
+                            speed: rng.gen_range(0.8..1.4),
+                            threshold: rng.gen(),
+                        },
+                    ));
+                }
+                parent.spawn((fill_node(FOG_COLOR.with_a(0.0)), FogLayer {}));
+                parent.spawn((fill_node(Color::NONE), LightningFlash { remaining: 0.0 }));
+            });
+    }
+}
+
+pub fn despawn_weather_overlay(
+    mut commands: Commands,
+    mut reader: EventReader<GameOver>,
+    overlay_query: Query<Entity, With<WeatherOverlay>>,
+) {
+    if let Some(_game_over) = reader.read().last() {
+        for overlay_entity in overlay_query.iter() {
+            commands.entity(overlay_entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn animate_rain(
+    weather: Res<Weather>,
+    time: Res<Time>,
+    mut drop_query: Query<(&mut RainDrop, &mut Style, &mut Visibility)>,
+) {
+    let rain = weather.rain();
+    // Screens per second, heavier rain falls faster and the wind drives it sideways
+    let fall = (0.9 + rain * 0.6) * time.delta_seconds();
+    let drift = weather.wind() * 0.5 * time.delta_seconds();
+    for (mut drop, mut style, mut visibility) in drop_query.iter_mut() {
+        if drop.threshold >= rain {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        drop.y = (drop.y + fall * drop.speed).fract();
+        drop.x = (drop.x + drift * drop.speed).rem_euclid(1.0);
+        style.left = Val::Percent(drop.x * 100.0);
+        style.top = Val::Percent(drop.y * 100.0);
+        *visibility = Visibility::Inherited;
+    }
+}
+
+pub fn show_fog(weather: Res<Weather>, mut fog_query: Query<&mut BackgroundColor, With<FogLayer>>) {
+    let color = FOG_COLOR.with_a(weather.fog() * MAX_FOG);
+    for mut background_color in fog_query.iter_mut() {
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
+
+pub fn flash_lightning(
+    weather: Res<Weather>,
+    time: Res<Time>,
+    mut flash_query: Query<(&mut LightningFlash, &mut BackgroundColor)>,
+) {
+    // Only the wind of a storm gets this strong
+    let storm = ((weather.wind() - 0.5) * 2.0).max(0.0);
+    let strike = thread_rng().gen::<f32>() < storm * LIGHTNING_CHANCE * time.delta_seconds();
+    for (mut flash, mut background_color) in flash_query.iter_mut() {
+        if strike {
+            flash.remaining = LIGHTNING_LENGTH;
+        }
+        flash.remaining = (flash.remaining - time.delta_seconds()).max(0.0);
+        background_color.0 = Color::rgba(1.0, 1.0, 0.95, flash.remaining / LIGHTNING_LENGTH * 0.6);
+    }
+}
+
+// Leans swaying structures back and forth, each a little out of step with its neighbours
+pub fn sway_structures(
+    weather: Res<Weather>,
+    time: Res<Time>,
+    mut sprite_query: Query<(&mut Transform, &StructureSprite, &AnimatedSprite)>,
+) {
+    let wind = weather.wind();
+    let elapsed = time.elapsed_seconds();
+    for (mut transform, structure_sprite, animated_sprite) in sprite_query.iter_mut() {
+        let AtlasKey::Structure(structure_type) = animated_sprite.key else {
+            continue;
+        };
+        let sway = structure_type.sway();
+        if sway == 0.0 {
+            continue;
+        }
+        let phase = (structure_sprite.anchor.x * 7 + structure_sprite.anchor.y * 13) as f32 * 0.37;
+        let swing = (elapsed * SWAY_SPEED * std::f32::consts::TAU * (0.5 + wind) + phase).sin();
+        // Leans downwind more than it swings back
+        let angle = -(sway * wind * (0.5 + 0.5 * swing)).to_radians();
+        let rotation = Quat::from_rotation_z(angle);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+fn fill_node(color: Color) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    }
+}
@@ -1,6 +1,11 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Clone, Copy)]
+use crate::world::registry::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
+
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Tile {
     pub tile_type: TileType,
     pub pos: Position,
@@ -9,14 +14,14 @@ pub struct Tile {
 impl Default for Tile {
     fn default() -> Self {
         Tile {
-            tile_type: TileType::Grass,
+            tile_type: TileType::named("Grass"),
             pos: Position::default(),
             structure: Structure::default()
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
@@ -31,23 +36,95 @@ impl Default for Position {
     }
 }
 
-#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "SavedStructure")]
 pub enum Structure {
-    Tree,
+    // The bottom left tile of a structure, which is the one that draws it
+    Anchor(StructureType),
+    // Another tile covered by a larger structure, this many tiles right and up from its anchor
+    Part(StructureType, u8, u8),
     #[default]
     None
 }
 
-#[derive(Debug, Clone, PartialEq, Copy)]
-pub enum TileType {
-    Ground,
-    Thud,
-    Grass,
-    Water,
+impl Structure {
+    pub fn structure_type(&self) -> Option<StructureType> {
+        match self {
+            Structure::Anchor(structure_type) | Structure::Part(structure_type, _, _) => Some(*structure_type),
+            Structure::None => None,
+        }
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        self.structure_type().map(|structure_type| structure_type.is_walkable()).unwrap_or(true)
+    }
+}
+
+// What a structure can be read from, including the palm trees of version 1 saves
+#[derive(Deserialize)]
+enum SavedStructure {
+    Anchor(StructureType),
+    Part(StructureType, u8, u8),
+    Tree(LegacyPalm),
+    None,
+}
+
+#[derive(Deserialize)]
+enum LegacyPalm {
+    Single,
+    Double,
+}
+
+impl From<SavedStructure> for Structure {
+    fn from(saved: SavedStructure) -> Self {
+        match saved {
+            SavedStructure::Anchor(structure_type) => Structure::Anchor(structure_type),
+            SavedStructure::Part(structure_type, x, y) => Structure::Part(structure_type, x, y),
+            SavedStructure::Tree(LegacyPalm::Single) => Structure::Anchor(StructureType::named("Palm")),
+            SavedStructure::Tree(LegacyPalm::Double) => Structure::Anchor(StructureType::named("DoublePalm")),
+            SavedStructure::None => Structure::None,
+        }
+    }
 }
 
 #[derive(Component)]
 pub struct ChunkLine {}
 
 #[derive(Component)]
-pub struct PalmTree {}
\ No newline at end of file
+pub struct StructureSprite {
+    pub anchor: IVec2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtlasKey {
+    Tile(TileType),
+    Structure(StructureType),
+}
+
+#[derive(Component)]
+pub struct AnimatedSprite {
+    pub key: AtlasKey,
+    pub frame_offset: usize,
+}
+#[derive(Component)]
+pub struct WorldChunk {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_1_palms_load_as_anchors() {
+        let structures: Vec<Structure> = ron::from_str("[Tree(Single), Tree(Double), None]").unwrap();
+        assert_eq!(
+            structures,
+            vec![
+                Structure::Anchor(StructureType::named("Palm")),
+                Structure::Anchor(StructureType::named("DoublePalm")),
+                Structure::None,
+            ]
+        );
+        let part: Structure = ron::from_str("Part(Hut, 1, 0)").unwrap();
+        assert_eq!(part, Structure::Part(StructureType::named("Hut"), 1, 0));
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use image::{Rgb, RgbImage};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::save::resources::*;
+use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::systems::*;
+use crate::world::tiles::*;
+use crate::world::utils::*;
+
+pub struct ExportOptions {
+    pub seed: u64,
+    pub generator: GeneratorKind,
+    // Width and height in chunks, centered on the origin chunk
+    pub size: UVec2,
+    pub out_dir: PathBuf,
+}
+
+impl ExportOptions {
+    // `--generate [out_dir] [--seed N] [--size W H] [--generator NAME]`, or None when not generating
+    pub fn from_args(args: &[String]) -> Result<Option<ExportOptions>, String> {
+        let Some(generate) = args.iter().position(|arg| arg == "--generate") else {
+            return Ok(None);
+        };
+        let mut options = ExportOptions {
+            seed: rand::random(),
+            generator: GeneratorKind::default(),
+            size: UVec2::new(10, 10),
+            out_dir: PathBuf::from("maps"),
+        };
+        if let Some(out_dir) = args.get(generate + 1).filter(|arg| !arg.starts_with("--")) {
+            options.out_dir = PathBuf::from(out_dir);
+        }
+
+        let parse = |index: usize, name: &str| -> Result<u32, String> {
+            args.get(index)
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("{} expects a number", name))
+        };
+        for (index, arg) in args.iter().enumerate() {
+            match arg.as_str() {
+                "--seed" => {
+                    options.seed = args
+                        .get(index + 1)
+                        .and_then(|seed| seed.parse().ok())
+                        .ok_or("--seed expects a number")?;
+                }
+                "--generator" => {
+                    options.generator = generator_from_arg(args.get(index + 1))?;
+                }
+                "--size" => {
+                    options.size = UVec2::new(parse(index + 1, "--size")?, parse(index + 2, "--size")?);
+                }
+                _ => {}
+            }
+        }
+        if options.size.x == 0 || options.size.y == 0 {
+            return Err("--size must be at least 1 1".to_string());
+        }
+        Ok(Some(options))
+    }
+
+    fn chunk_min(&self) -> IVec2 {
+        -(self.size / 2).as_ivec2()
+    }
+
+    fn chunk_max(&self) -> IVec2 {
+        self.chunk_min() + self.size.as_ivec2() - IVec2::ONE
+    }
+}
+
+pub fn generator_from_arg(arg: Option<&String>) -> Result<GeneratorKind, String> {
+    arg.and_then(|name| GeneratorKind::from_name(name)).ok_or_else(|| {
+        let names: Vec<&str> = GeneratorKind::ALL.iter().map(|kind| kind.name()).collect();
+        format!("--generator expects one of {}", names.join(", "))
+    })
+}
+
+#[derive(Serialize)]
+struct WorldExport {
+    seed: u64,
+    chunk_min: (i32, i32),
+    chunk_max: (i32, i32),
+    // Rows from the top (highest y) down, matching the preview image
+    chunk_biomes: Vec<Vec<TileType>>,
+    world: SavedWorld,
+}
+
+pub fn export_world(options: &ExportOptions) -> Result<Vec<PathBuf>, String> {
+    let (chunk_min, chunk_max) = (options.chunk_min(), options.chunk_max());
+
+    load_tile_and_structure_definitions()?;
+    let worldgen_config = load_worldgen_config()?;
+    let mut the_world = TheWorld::default();
+    the_world.reset(options.seed, options.generator);
+    for y in chunk_min.y..=chunk_max.y {
+        for x in chunk_min.x..=chunk_max.x {
+            let chunk_pos = IVec2::new(x, y);
+            the_world.chunks.insert(chunk_pos, the_world.generate_chunk(&worldgen_config, chunk_pos));
+        }
+    }
+
+    fs::create_dir_all(&options.out_dir).map_err(|err| err.to_string())?;
+    let name = format!("world_{}_{}", options.generator.name(), options.seed);
+    let image_path = options.out_dir.join(format!("{}.png", name));
+    let data_path = options.out_dir.join(format!("{}.ron", name));
+
+    preview_image(&the_world, chunk_min, chunk_max)
+        .save(&image_path)
+        .map_err(|err| err.to_string())?;
+
+    let export = WorldExport {
+        seed: options.seed,
+        chunk_min: (chunk_min.x, chunk_min.y),
+        chunk_max: (chunk_max.x, chunk_max.y),
+        chunk_biomes: (chunk_min.y..=chunk_max.y)
+            .rev()
+            .map(|y| {
+                (chunk_min.x..=chunk_max.x)
+                    .map(|x| the_world.chunks[&IVec2::new(x, y)].biome)
+                    .collect()
+            })
+            .collect(),
+        world: SavedWorld::from(&the_world),
+    };
+    // Deeper levels stay on one line so every tile row is a single diffable line
+    let pretty = ron::ser::PrettyConfig::default().depth_limit(5);
+    let contents = ron::ser::to_string_pretty(&export, pretty).map_err(|err| err.to_string())?;
+    fs::write(&data_path, contents).map_err(|err| err.to_string())?;
+
+    Ok(vec![image_path, data_path])
+}
+
+// One pixel per tile with world y pointing up, so the image matches the game view
+fn preview_image(the_world: &TheWorld, chunk_min: IVec2, chunk_max: IVec2) -> RgbImage {
+    let tile_min = chunk_origin(chunk_min);
+    let tile_max = chunk_origin(chunk_max) + IVec2::splat(CHUNK_SIZE - 1);
+    let size = (tile_max - tile_min + IVec2::ONE).as_uvec2();
+
+    RgbImage::from_fn(size.x, size.y, |x, y| {
+        let tile_pos = IVec2::new(tile_min.x + x as i32, tile_max.y - y as i32);
+        match the_world.get_tile(tile_pos) {
+            Some(tile) => match tile.structure.structure_type() {
+                Some(structure_type) => structure_color(structure_type),
+                None => tile_color(tile.tile_type),
+            },
+            None => Rgb([0, 0, 0]),
+        }
+    })
+}
+
+fn tile_color(tile_type: TileType) -> Rgb<u8> {
+    let (r, g, b) = tile_type.with_definition(|definition| definition.color);
+    Rgb([r, g, b])
+}
+
+fn structure_color(structure_type: StructureType) -> Rgb<u8> {
+    let (r, g, b) = structure_type.definition().color;
+    Rgb([r, g, b])
+}
@@ -0,0 +1,408 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::world::components::*;
+use crate::world::noise::*;
+use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::systems::*;
+use crate::world::tiles::*;
+use crate::world::utils::*;
+
+// Every generation stage draws from its own stream so a chunk never depends
+// on how many numbers another stage (or another chunk) consumed.
+pub const INITIAL_BIOME_STAGE: u64 = 1;
+pub const BIOME_STAGE: u64 = 2;
+pub const BLEND_STAGE: u64 = 3;
+pub const FILL_STAGE: u64 = 4;
+// The noise generator's palms, kept on the stream they were drawn from before passes existed
+pub const TREE_STAGE: u64 = 5;
+pub const TERRAIN_STAGE: u64 = 6;
+
+pub trait ChunkGenerator: Send + Sync {
+    // Must only depend on the seed and position so chunks can be generated in any order
+    fn generate_chunk(&self, seed: u64, chunk: IVec2) -> Chunk;
+}
+
+// The chunk being generated, handed from one pass to the next
+pub struct ChunkContext {
+    pub seed: u64,
+    pub chunk: IVec2,
+    pub biome: TileType,
+    pub tiles: Vec<Vec<Tile>>,
+    // Unblended biomes of the surrounding 3x3 chunks, when a pass picked biomes per chunk
+    pub neighbor_biomes: Option<[[TileType; 3]; 3]>,
+    // Moisture of every tile in -1.0..=1.0, when a pass classified tiles from noise
+    pub moisture: Option<Vec<Vec<f32>>>,
+}
+
+pub trait WorldGenPass: Send + Sync {
+    // Seeds the RNG handed to `apply`, keep it stable so existing seeds keep their worlds
+    fn stage(&self) -> u64;
+    fn apply(&self, context: &mut ChunkContext, rng: &mut StdRng);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PassConfig {
+    ChunkVote(ChunkVotePass),
+    Blend(BlendPass),
+    NoiseTerrain(NoiseTerrainPass),
+    Structures(StructurePass),
+}
+
+impl PassConfig {
+    pub fn pass(&self) -> &dyn WorldGenPass {
+        match self {
+            PassConfig::ChunkVote(pass) => pass,
+            PassConfig::Blend(pass) => pass,
+            PassConfig::NoiseTerrain(pass) => pass,
+            PassConfig::Structures(pass) => pass,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PassEntry {
+    pub pass: PassConfig,
+    #[serde(default = "pass_enabled_default")]
+    pub enabled: bool,
+}
+
+fn pass_enabled_default() -> bool {
+    true
+}
+
+impl From<PassConfig> for PassEntry {
+    fn from(pass: PassConfig) -> Self {
+        PassEntry { pass, enabled: true }
+    }
+}
+
+// Runs its passes in order over a chunk that starts out as plain grass
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct WorldGenPipeline {
+    pub passes: Vec<PassEntry>,
+}
+
+impl ChunkGenerator for WorldGenPipeline {
+    fn generate_chunk(&self, seed: u64, chunk: IVec2) -> Chunk {
+        let mut context = ChunkContext {
+            seed,
+            chunk,
+            biome: TileType::named("Grass"),
+            tiles: filled_chunk_tiles(chunk, TileType::named("Grass")),
+            neighbor_biomes: None,
+            moisture: None,
+        };
+
+        for (index, entry) in self.passes.iter().enumerate() {
+            if !entry.enabled {
+                continue;
+            }
+            let pass = entry.pass.pass();
+            // Repeats of the same pass get their own stream, the first keeps the plain stage
+            let repeats = self.passes[..index]
+                .iter()
+                .filter(|earlier| earlier.enabled && earlier.pass.pass().stage() == pass.stage())
+                .count() as u64;
+            let mut rng = chunk_rng(seed, chunk, pass.stage() + repeats * 16);
+            pass.apply(&mut context, &mut rng);
+        }
+
+        Chunk {
+            biome: context.biome,
+            tiles: context.tiles,
+        }
+    }
+}
+
+pub fn chunk_rng(seed: u64, chunk: IVec2, stage: u64) -> StdRng {
+    let mut hash = splitmix64(seed ^ stage.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    hash = splitmix64(hash ^ chunk.x as u32 as u64);
+    hash = splitmix64(hash ^ ((chunk.y as u32 as u64) << 32));
+    StdRng::seed_from_u64(hash)
+}
+
+pub fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn filled_chunk_tiles(chunk: IVec2, tile_type: TileType) -> Vec<Vec<Tile>> {
+    (0..CHUNK_SIZE)
+        .map(|y| {
+            (0..CHUNK_SIZE)
+                .map(|x| Tile {
+                    tile_type,
+                    pos: tile_position(chunk_origin(chunk) + IVec2::new(x, y)),
+                    structure: Structure::None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Picks one biome per chunk by letting neighbouring chunks vote, weighted by
+// the spawn weights of the tile definitions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ChunkVotePass {
+    pub potential_biomes_multi: f32,
+    pub random_biome_chance: f32,
+    pub water_chance: f32,
+    pub water_biome: TileType,
+}
+
+impl Default for ChunkVotePass {
+    fn default() -> Self {
+        ChunkVotePass {
+            potential_biomes_multi: 100.9,
+            random_biome_chance: 0.0001,
+            water_chance: 0.005,
+            water_biome: TileType::named("Water"),
+        }
+    }
+}
+
+impl ChunkVotePass {
+    fn initial_biome<'a>(&self, seed: u64, chunk: IVec2, definitions: &'a [TileDefinition]) -> &'a TileDefinition {
+        let mut rng = chunk_rng(seed, chunk, INITIAL_BIOME_STAGE);
+        &definitions[rng.gen_range(0..definitions.len())]
+    }
+
+    // Uses its own stream rather than the pass RNG, because neighbours recompute it too
+    fn chunk_biome(&self, seed: u64, chunk: IVec2, definitions: &[TileDefinition]) -> TileType {
+        let mut rng = chunk_rng(seed, chunk, BIOME_STAGE);
+        let mut potential_biomes: Vec<TileType> = vec![];
+
+        for offset in [IVec2::NEG_X, IVec2::NEG_Y, IVec2::X, IVec2::Y] {
+            let neighbor_biome = self.initial_biome(seed, chunk + offset, definitions);
+            if neighbor_biome.spawn_weight > 0.0 {
+                let votes = rng.gen::<f32>() * self.potential_biomes_multi * neighbor_biome.spawn_weight;
+                for _ in 0..=votes as usize {
+                    potential_biomes.push(neighbor_biome.name);
+                }
+            }
+        }
+
+        potential_biomes.shuffle(&mut rng);
+
+        if rng.gen::<f32>() < self.water_chance {
+            self.water_biome
+        } else if rng.gen::<f32>() < self.random_biome_chance || potential_biomes.is_empty() {
+            definitions
+                .choose_weighted(&mut rng, |definition| definition.spawn_weight)
+                .map(|definition| definition.name)
+                .unwrap_or(self.water_biome)
+        } else {
+            *potential_biomes.choose(&mut rng).unwrap()
+        }
+    }
+}
+
+impl WorldGenPass for ChunkVotePass {
+    fn stage(&self) -> u64 {
+        BIOME_STAGE
+    }
+
+    fn apply(&self, context: &mut ChunkContext, _rng: &mut StdRng) {
+        let definitions = tile_definitions();
+        if definitions.is_empty() {
+            return;
+        }
+        let mut neighbor_biomes = [[context.biome; 3]; 3];
+        for (dy, row) in neighbor_biomes.iter_mut().enumerate() {
+            for (dx, biome) in row.iter_mut().enumerate() {
+                let offset = IVec2::new(dx as i32 - 1, dy as i32 - 1);
+                *biome = self.chunk_biome(context.seed, context.chunk + offset, &definitions);
+            }
+        }
+        context.biome = neighbor_biomes[1][1];
+        context.neighbor_biomes = Some(neighbor_biomes);
+        for tile in context.tiles.iter_mut().flatten() {
+            tile.tile_type = context.biome;
+        }
+    }
+}
+
+// Blending only ever looks at the unblended chunk biomes, never at tiles that
+// were already blended, so both sides of a chunk border agree on the result.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct BlendPass {
+    pub range: i32,
+    pub chance: f32,
+    // How much the chance drops for every tile away from the other biome
+    pub falloff: f32,
+}
+
+impl Default for BlendPass {
+    fn default() -> Self {
+        BlendPass {
+            range: 5,
+            chance: 0.41,
+            falloff: 0.125,
+        }
+    }
+}
+
+impl WorldGenPass for BlendPass {
+    fn stage(&self) -> u64 {
+        BLEND_STAGE
+    }
+
+    fn apply(&self, context: &mut ChunkContext, rng: &mut StdRng) {
+        let Some(neighbor_biomes) = context.neighbor_biomes else {
+            return;
+        };
+        let blend_range = -self.range..=self.range;
+        let tile_types = tile_types();
+
+        let unblended_tile_type = |x: i32, y: i32| -> TileType {
+            neighbor_biomes[(y.div_euclid(CHUNK_SIZE) + 1) as usize][(x.div_euclid(CHUNK_SIZE) + 1) as usize]
+        };
+
+        for y in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let current_tile_type: TileType = unblended_tile_type(x, y);
+
+                if !current_tile_type.blends() {
+                    continue;
+                }
+                let mut possible_tile_types = tile_types.clone();
+                possible_tile_types.shuffle(rng);
+
+                for &possible_tile_type in &possible_tile_types {
+                    if possible_tile_type == current_tile_type {
+                        continue;
+                    }
+
+                    let mut is_near_desired_chunk = false;
+
+                    'outer: for (dy, row) in neighbor_biomes.iter().enumerate() {
+                        for (dx, &neighbor_biome) in row.iter().enumerate() {
+                            if dx == 1 && dy == 1 {
+                                continue;
+                            }
+                            if neighbor_biome == possible_tile_type {
+                                is_near_desired_chunk = true;
+                                break 'outer;
+                            }
+                        }
+                    }
+
+                    if is_near_desired_chunk {
+                        let mut tile_distance = i32::MAX;
+
+                        for d in blend_range.clone() {
+                            if unblended_tile_type(x + d, y) == possible_tile_type
+                                || unblended_tile_type(x, y + d) == possible_tile_type
+                            {
+                                tile_distance = tile_distance.min(d.abs());
+                            }
+                        }
+
+                        if blend_range.contains(&tile_distance) {
+                            let chance: f32 = self.chance - tile_distance as f32 * self.falloff;
+                            if rng.gen::<f32>() < chance {
+                                context.tiles[y as usize][x as usize].tile_type = possible_tile_type;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Scatters the structures listed in the tile definitions, at their density.
+// A structure only goes where its whole footprint fits inside the chunk on
+// free, walkable tiles, so chunks never depend on their neighbours.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StructurePass {
+    // Use the chunk biome's structures for every tile, so blended edges match their chunk
+    pub use_chunk_biome: bool,
+    pub density_scale: f32,
+    // Scales the density by `1.0 + moisture_weight * moisture` where the tiles have a moisture
+    pub moisture_weight: f32,
+    // Draws from another stage's stream instead of `FILL_STAGE`
+    pub stage: Option<u64>,
+}
+
+impl Default for StructurePass {
+    fn default() -> Self {
+        StructurePass {
+            use_chunk_biome: false,
+            density_scale: 1.0,
+            moisture_weight: 0.0,
+            stage: None,
+        }
+    }
+}
+
+impl WorldGenPass for StructurePass {
+    fn stage(&self) -> u64 {
+        self.stage.unwrap_or(FILL_STAGE)
+    }
+
+    fn apply(&self, context: &mut ChunkContext, rng: &mut StdRng) {
+        let definitions = tile_definitions();
+        for y in 0..CHUNK_SIZE as usize {
+            for x in 0..CHUNK_SIZE as usize {
+                let source = if self.use_chunk_biome {
+                    context.biome
+                } else {
+                    context.tiles[y][x].tile_type
+                };
+                let Some(definition) = definitions.iter().find(|definition| definition.name == source) else {
+                    continue;
+                };
+                if definition.structures.is_empty() || definition.structure_density <= 0.0 {
+                    continue;
+                }
+                let moisture = context.moisture.as_ref().map(|moisture| moisture[y][x]).unwrap_or(0.0);
+                let density = definition.structure_density * self.density_scale * (1.0 + self.moisture_weight * moisture);
+                if rng.gen::<f32>() >= density {
+                    continue;
+                }
+                if let Some(structure_type) = definition.pick_structure(rng.gen()) {
+                    place_structure(&mut context.tiles, x, y, structure_type);
+                }
+            }
+        }
+    }
+}
+
+// Returns whether the structure fit, leaving the tiles untouched when it did not
+pub fn place_structure(tiles: &mut [Vec<Tile>], x: usize, y: usize, structure_type: StructureType) -> bool {
+    let (width, height) = structure_type.footprint();
+    let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+    if y + height > tiles.len() || x + width > tiles[y].len() {
+        return false;
+    }
+    let fits = tiles[y..y + height].iter().all(|row| {
+        row[x..x + width]
+            .iter()
+            .all(|tile| tile.structure == Structure::None && tile.tile_type.is_walkable())
+    });
+    if !fits {
+        return false;
+    }
+
+    for (dy, row) in tiles[y..y + height].iter_mut().enumerate() {
+        for (dx, tile) in row[x..x + width].iter_mut().enumerate() {
+            tile.structure = if dx == 0 && dy == 0 {
+                Structure::Anchor(structure_type)
+            } else {
+                Structure::Part(structure_type, dx as u8, dy as u8)
+            };
+        }
+    }
+    true
+}
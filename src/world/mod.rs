@@ -1,9 +1,15 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, transform::TransformSystem};
 
-mod components;
-mod resources;
-mod systems;
-mod utils;
+pub mod components;
+pub mod export;
+mod generation;
+mod noise;
+pub mod registry;
+pub mod resources;
+pub mod structures;
+pub mod systems;
+pub mod tiles;
+pub mod utils;
 
 use crate::resources::*;
 use resources::*;
@@ -14,12 +20,37 @@ pub struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TheWorld>()
+            .init_resource::<LoadedChunks>()
+            .init_resource::<ChunkStreaming>()
+            .init_resource::<TileAnimationClock>()
             .init_state::<ChunkLineRenderState>()
+            .add_systems(Startup, (load_worldgen, (load_world_definitions, load_tile_textures).chain()))
+            .add_systems(OnEnter(GameState::Game), create_world)
+            .add_systems(OnEnter(GameState::Menu), despawn_world)
             .add_systems(
-                OnEnter(GameState::Game),
-                (create_world.before(render_world), render_world),
+                Update,
+                (
+                    build_tile_atlas.run_if(resource_exists::<TileTextures>),
+                    toggle_chunk_outlines.run_if(in_state(GameState::Game).or_else(in_state(GameState::Editor))),
+                    (
+                        advance_animation_clock,
+                        stream_chunks,
+                        refresh_dirty_tiles,
+                        animate_chunk_tiles,
+                        animate_sprites,
+                    )
+                        .chain()
+                        .run_if(
+                            in_state(GameState::Game)
+                                .or_else(in_state(GameState::Editor))
+                                .and_then(resource_exists::<TileAtlas>),
+                        ),
+                ),
             )
-            .add_systems(OnEnter(GameState::Menu), despawn_world)
-            .add_systems(Update, toggle_chunk_outlines.run_if(in_state(GameState::Game)));
+            .add_systems(PostUpdate, y_sort.before(TransformSystem::TransformPropagate));
+
+        #[cfg(debug_assertions)]
+        app.init_resource::<DefinitionsWatch>()
+            .add_systems(Update, reload_world_definitions);
     }
 }
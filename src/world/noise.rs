@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use crate::world::generation::*;
+use crate::world::systems::*;
+use crate::world::tiles::*;
+use crate::world::utils::*;
+
+// Every layer hashes its own salt into the seed so the layers are uncorrelated
+const ELEVATION_SALT: u64 = 0x454C_4556;
+const MOISTURE_SALT: u64 = 0x4D4F_4953;
+const TEMPERATURE_SALT: u64 = 0x5445_4D50;
+const RIVER_SALT: u64 = 0x5249_5645;
+const LAKE_SALT: u64 = 0x4C41_4B45;
+
+// Classifies every tile from layered noise, so regions follow the terrain
+// instead of chunk borders and water forms coasts, lakes and rivers.
+// Scales are in tiles per noise cell, levels are in the noise range -1.0..=1.0.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct NoiseTerrainPass {
+    pub elevation_scale: f32,
+    pub moisture_scale: f32,
+    pub temperature_scale: f32,
+    pub river_scale: f32,
+    pub lake_scale: f32,
+    pub sea_level: f32,
+    pub mountain_level: f32,
+    pub river_width: f32,
+    pub lake_level: f32,
+    pub grass_moisture: f32,
+    pub water_tile: TileType,
+    pub mountain_tile: TileType,
+    pub grass_tile: TileType,
+    pub dry_tile: TileType,
+}
+
+impl Default for NoiseTerrainPass {
+    fn default() -> Self {
+        NoiseTerrainPass {
+            elevation_scale: 160.0,
+            moisture_scale: 120.0,
+            temperature_scale: 300.0,
+            river_scale: 220.0,
+            lake_scale: 60.0,
+            sea_level: -0.25,
+            mountain_level: 0.3,
+            river_width: 0.025,
+            lake_level: 0.55,
+            grass_moisture: -0.1,
+            water_tile: TileType::named("Water"),
+            mountain_tile: TileType::named("Thud"),
+            grass_tile: TileType::named("Grass"),
+            dry_tile: TileType::named("Ground"),
+        }
+    }
+}
+
+impl WorldGenPass for NoiseTerrainPass {
+    fn stage(&self) -> u64 {
+        TERRAIN_STAGE
+    }
+
+    fn apply(&self, context: &mut ChunkContext, _rng: &mut StdRng) {
+        let mut counts: HashMap<TileType, usize> = HashMap::new();
+        let mut moisture = vec![vec![0.0; CHUNK_SIZE as usize]; CHUNK_SIZE as usize];
+
+        for (y, row) in context.tiles.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                let tile_pos = chunk_origin(context.chunk) + IVec2::new(x as i32, y as i32);
+                (tile.tile_type, moisture[y][x]) = self.classify_tile(context.seed, tile_pos);
+                *counts.entry(tile.tile_type).or_insert(0) += 1;
+            }
+        }
+        context.moisture = Some(moisture);
+
+        // The most common tile stands in for the chunk biome, ties go to the earlier biome
+        if let Some(biome) = tile_types()
+            .into_iter()
+            .rev()
+            .max_by_key(|tile_type| counts.get(tile_type).copied().unwrap_or(0))
+        {
+            context.biome = biome;
+        }
+    }
+}
+
+impl NoiseTerrainPass {
+    // Returns the tile type and the moisture it was classified with
+    fn classify_tile(&self, seed: u64, tile_pos: IVec2) -> (TileType, f32) {
+        let pos = tile_pos.as_vec2();
+        let elevation = fbm(seed ^ ELEVATION_SALT, pos / self.elevation_scale, 5);
+        let moisture = fbm(seed ^ MOISTURE_SALT, pos / self.moisture_scale, 4);
+        let temperature =
+            fbm(seed ^ TEMPERATURE_SALT, pos / self.temperature_scale, 3) - elevation * 0.5;
+
+        if elevation < self.sea_level {
+            return (self.water_tile, moisture);
+        }
+        // Rivers follow the zero crossings of a noise field, which form long winding lines
+        let river = fbm(seed ^ RIVER_SALT, pos / self.river_scale, 3).abs();
+        if river < self.river_width && elevation < self.mountain_level {
+            return (self.water_tile, moisture);
+        }
+        let lake = fbm(seed ^ LAKE_SALT, pos / self.lake_scale, 2) + moisture * 0.25;
+        if lake > self.lake_level && elevation < self.mountain_level {
+            return (self.water_tile, moisture);
+        }
+
+        let tile_type = if elevation > self.mountain_level {
+            self.mountain_tile
+        } else if moisture - temperature * 0.3 > self.grass_moisture {
+            self.grass_tile
+        } else {
+            self.dry_tile
+        };
+        (tile_type, moisture)
+    }
+}
+
+// Fractal gradient noise in roughly -1.0..=1.0
+pub fn fbm(seed: u64, pos: Vec2, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_amplitude = 0.0;
+    for octave in 0..octaves {
+        total += gradient_noise(seed.wrapping_add(octave as u64), pos * frequency) * amplitude;
+        max_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / max_amplitude
+}
+
+fn gradient_noise(seed: u64, pos: Vec2) -> f32 {
+    let cell = pos.floor();
+    let offset = pos - cell;
+    let cell = cell.as_ivec2();
+
+    let corner = |dx: i32, dy: i32| -> f32 {
+        gradient(seed, cell + IVec2::new(dx, dy)).dot(offset - Vec2::new(dx as f32, dy as f32))
+    };
+    let fade = offset * offset * offset * (offset * (offset * 6.0 - 15.0) + 10.0);
+
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * fade.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * fade.x;
+    // Scaled so the output uses most of -1.0..=1.0
+    ((bottom + (top - bottom) * fade.y) * 1.4).clamp(-1.0, 1.0)
+}
+
+fn gradient(seed: u64, cell: IVec2) -> Vec2 {
+    let hash = splitmix64(seed ^ splitmix64(cell.x as u32 as u64 | ((cell.y as u32 as u64) << 32)));
+    Vec2::from_angle((hash >> 40) as f32 / (1u64 << 24) as f32 * TAU)
+}
@@ -0,0 +1,164 @@
+use serde::de::{self, DeserializeOwned, EnumAccess, VariantAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::{Mutex, RwLock};
+
+// Names interned into small ids, each with an optional definition. Tile and
+// structure types are ids into one of these, so they stay `Copy` and cheap to
+// compare while the sets themselves come from asset files.
+pub struct Registry<D> {
+    names: Vec<&'static str>,
+    // Indexed by id, names without a definition fall back to a default one
+    definitions: Vec<Option<D>>,
+    // Defined ids in file order
+    order: Vec<u16>,
+}
+
+impl<D> Registry<D> {
+    pub const fn new() -> Self {
+        Registry {
+            names: Vec::new(),
+            definitions: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    pub fn name(&self, id: u16) -> &'static str {
+        self.names[id as usize]
+    }
+
+    pub fn definition(&self, id: u16) -> Option<&D> {
+        self.definitions.get(id as usize).and_then(|definition| definition.as_ref())
+    }
+
+    pub fn order(&self) -> &[u16] {
+        &self.order
+    }
+
+    // Replaces every definition, a repeated id keeps its first definition
+    pub fn set_definitions(&mut self, definitions: Vec<(u16, D)>) {
+        self.definitions.iter_mut().for_each(|definition| *definition = None);
+        self.order.clear();
+        for (id, definition) in definitions {
+            if self.order.contains(&id) {
+                continue;
+            }
+            self.order.push(id);
+            self.definitions[id as usize] = Some(definition);
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<u16> {
+        self.names.iter().position(|known| *known == name).map(|index| index as u16)
+    }
+}
+
+// Unknown names are registered on first use, so a save can mention a type the definitions dropped
+pub fn intern<D>(registry: &RwLock<Registry<D>>, name: &str) -> u16 {
+    if let Some(id) = registry.read().unwrap().find(name) {
+        return id;
+    }
+    let mut registry = registry.write().unwrap();
+    if let Some(id) = registry.find(name) {
+        return id;
+    }
+    registry.names.push(pooled_name(name));
+    registry.definitions.push(None);
+    (registry.names.len() - 1) as u16
+}
+
+// Names are handed out as `&'static str`, so each distinct name is leaked exactly
+// once for the whole process and shared by every registry that mentions it
+static NAME_POOL: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+fn pooled_name(name: &str) -> &'static str {
+    let mut pool = NAME_POOL.lock().unwrap();
+    if let Some(pooled) = pool.get(name) {
+        return pooled;
+    }
+    let pooled: &'static str = Box::leak(name.to_string().into_boxed_str());
+    pool.insert(pooled);
+    pooled
+}
+
+pub fn load_definitions<D: DeserializeOwned>(path: &Path) -> Result<Vec<D>, String> {
+    let definitions: Vec<D> = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    if definitions.is_empty() {
+        return Err(format!("{}: nothing defined", path.display()));
+    }
+    Ok(definitions)
+}
+
+pub trait Named: Copy {
+    fn named(name: &str) -> Self;
+    fn name(&self) -> &'static str;
+}
+
+// Written like a unit variant, so files and saves name types as bare identifiers
+pub fn serialize_named<T: Named, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_unit_variant("Named", 0, value.name())
+}
+
+// Read like a unit variant of an enum whose variants are only known at runtime
+pub fn deserialize_named<'de, T: Named, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    deserializer.deserialize_enum("Named", &[], NameVisitor(PhantomData))
+}
+
+struct NameVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Named> Visitor<'de> for NameVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a name")
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<T, E> {
+        Ok(T::named(name))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<T, A::Error> {
+        let (Identifier(value), variant) = data.variant()?;
+        variant.unit_variant()?;
+        Ok(value)
+    }
+}
+
+// Variant names have to be read as identifiers, RON refuses to read them as strings
+struct Identifier<T>(T);
+
+impl<'de, T: Named> Deserialize<'de> for Identifier<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_identifier(NameVisitor(PhantomData))
+            .map(Identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reinterning_reuses_ids_and_names() {
+        static FIRST: RwLock<Registry<()>> = RwLock::new(Registry::new());
+        static SECOND: RwLock<Registry<()>> = RwLock::new(Registry::new());
+
+        let id = intern(&FIRST, "Lantern");
+        FIRST.write().unwrap().set_definitions(vec![(id, ())]);
+        // A reload interns the same names again
+        assert_eq!(intern(&FIRST, "Lantern"), id);
+        FIRST.write().unwrap().set_definitions(vec![(id, ())]);
+        assert_eq!(FIRST.read().unwrap().names.len(), 1);
+
+        let other = intern(&SECOND, "Lantern");
+        assert!(std::ptr::eq(FIRST.read().unwrap().name(id), SECOND.read().unwrap().name(other)));
+    }
+}
@@ -1,5 +1,13 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
 use crate::world::components::*;
+use crate::world::generation::*;
+use crate::world::noise::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
+use crate::world::utils::*;
 
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
 pub enum ChunkLineRenderState {
@@ -8,24 +16,297 @@ pub enum ChunkLineRenderState {
     On,
 }
 
-#[derive(Resource, Clone)]
-pub struct TheWorld {
-    pub world: Vec<Vec<Tile>>,
-    pub chunk_biomes: Vec<Vec<TileType>>
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeneratorKind {
+    #[default]
+    ChunkVote,
+    Noise,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 2] = [GeneratorKind::ChunkVote, GeneratorKind::Noise];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorKind::ChunkVote => "chunk-vote",
+            GeneratorKind::Noise => "noise",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GeneratorKind> {
+        GeneratorKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn next(&self) -> GeneratorKind {
+        let index = GeneratorKind::ALL.iter().position(|kind| kind == self).unwrap();
+        GeneratorKind::ALL[(index + 1) % GeneratorKind::ALL.len()]
+    }
+}
+
+// Loaded from `assets/worldgen.ron`, the defaults mirror the shipped file
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WorldGenConfig {
+    pub chunk_vote: WorldGenPipeline,
+    pub noise: WorldGenPipeline,
 }
 
-impl Default for TheWorld {
+impl Default for WorldGenConfig {
     fn default() -> Self {
-        TheWorld {
-            world: Vec::new(),
-            chunk_biomes: Vec::new(),
+        WorldGenConfig {
+            chunk_vote: WorldGenPipeline {
+                passes: vec![
+                    PassConfig::ChunkVote(ChunkVotePass::default()).into(),
+                    PassConfig::Blend(BlendPass::default()).into(),
+                    PassConfig::Structures(StructurePass {
+                        use_chunk_biome: true,
+                        ..default()
+                    })
+                    .into(),
+                ],
+            },
+            noise: WorldGenPipeline {
+                passes: vec![
+                    PassConfig::NoiseTerrain(NoiseTerrainPass::default()).into(),
+                    // Wetter grass grows denser palms
+                    PassConfig::Structures(StructurePass {
+                        density_scale: 1.25,
+                        moisture_weight: 1.0,
+                        stage: Some(TREE_STAGE),
+                        ..default()
+                    })
+                    .into(),
+                ],
+            },
         }
     }
 }
 
+impl WorldGenConfig {
+    pub fn pipeline(&self, generator: GeneratorKind) -> &WorldGenPipeline {
+        match generator {
+            GeneratorKind::ChunkVote => &self.chunk_vote,
+            GeneratorKind::Noise => &self.noise,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Chunk {
+    pub biome: TileType,
+    pub tiles: Vec<Vec<Tile>>,
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct TheWorld {
+    pub seed: u64,
+    pub generator: GeneratorKind,
+    pub chunks: HashMap<IVec2, Chunk>,
+    pub dirty_tiles: Vec<IVec2>,
+}
+
 impl TheWorld {
-    pub fn new(&mut self, world: Vec<Vec<Tile>>, chunk_biomes: Vec<Vec<TileType>>) {
-        self.world = world;
-        self.chunk_biomes = chunk_biomes;
+    pub fn reset(&mut self, seed: u64, generator: GeneratorKind) {
+        self.seed = seed;
+        self.generator = generator;
+        self.chunks.clear();
+        self.dirty_tiles.clear();
+    }
+
+    pub fn generate_chunk(&self, worldgen_config: &WorldGenConfig, chunk: IVec2) -> Chunk {
+        worldgen_config
+            .pipeline(self.generator)
+            .generate_chunk(self.seed, chunk)
+    }
+
+    pub fn get_tile(&self, tile_pos: IVec2) -> Option<&Tile> {
+        let local = tile_to_local(tile_pos);
+        self.chunks
+            .get(&tile_to_chunk(tile_pos))
+            .map(|chunk| &chunk.tiles[local.y as usize][local.x as usize])
+    }
+
+    // Changing a tile through here queues it so the chunk mesh is patched in place
+    pub fn set_tile(&mut self, tile_pos: IVec2, tile: Tile) {
+        let local = tile_to_local(tile_pos);
+        if let Some(chunk) = self.chunks.get_mut(&tile_to_chunk(tile_pos)) {
+            chunk.tiles[local.y as usize][local.x as usize] = tile;
+            self.dirty_tiles.push(tile_pos);
+        }
+    }
+
+    // The anchor tile and type of the structure covering a tile
+    pub fn structure_at(&self, tile_pos: IVec2) -> Option<(IVec2, StructureType)> {
+        match self.get_tile(tile_pos)?.structure {
+            Structure::Anchor(structure_type) => Some((tile_pos, structure_type)),
+            Structure::Part(structure_type, dx, dy) => {
+                Some((tile_pos - IVec2::new(dx as i32, dy as i32), structure_type))
+            }
+            Structure::None => None,
+        }
+    }
+
+    pub fn chunk_biome(&self, tile_pos: IVec2) -> Option<TileType> {
+        self.chunks.get(&tile_to_chunk(tile_pos)).map(|chunk| chunk.biome)
+    }
+
+    // Changes the ground of a tile, tiles under a structure are left alone
+    pub fn set_tile_type(&mut self, tile_pos: IVec2, tile_type: TileType) -> bool {
+        let Some(mut tile) = self.get_tile(tile_pos).copied() else {
+            return false;
+        };
+        if tile.tile_type == tile_type || tile.structure != Structure::None {
+            return false;
+        }
+        tile.tile_type = tile_type;
+        self.set_tile(tile_pos, tile);
+        true
+    }
+
+    // Unlike world generation this may cross chunk borders, as long as every chunk is loaded
+    pub fn can_add_structure(&self, anchor: IVec2, structure_type: StructureType) -> bool {
+        footprint_tiles(anchor, structure_type).all(|(tile_pos, _, _)| {
+            self.get_tile(tile_pos)
+                .is_some_and(|tile| tile.structure == Structure::None && tile.tile_type.is_walkable())
+        })
+    }
+
+    pub fn add_structure(&mut self, anchor: IVec2, structure_type: StructureType) -> bool {
+        if !self.can_add_structure(anchor, structure_type) {
+            return false;
+        }
+        for (tile_pos, dx, dy) in footprint_tiles(anchor, structure_type) {
+            if let Some(mut tile) = self.get_tile(tile_pos).copied() {
+                tile.structure = if dx == 0 && dy == 0 {
+                    Structure::Anchor(structure_type)
+                } else {
+                    Structure::Part(structure_type, dx, dy)
+                };
+                self.set_tile(tile_pos, tile);
+            }
+        }
+        true
     }
-}
\ No newline at end of file
+
+    // Clears every tile of the structure covering a tile
+    pub fn remove_structure(&mut self, tile_pos: IVec2) -> Option<StructureType> {
+        let (anchor, structure_type) = self.structure_at(tile_pos)?;
+        for (covered, _, _) in footprint_tiles(anchor, structure_type) {
+            if let Some(mut tile) = self.get_tile(covered).copied() {
+                if tile.structure.structure_type() == Some(structure_type) {
+                    tile.structure = Structure::None;
+                    self.set_tile(covered, tile);
+                }
+            }
+        }
+        Some(structure_type)
+    }
+
+    // Tiles that have not been generated yet act as the edge of the world
+    pub fn is_walkable(&self, tile_pos: IVec2) -> bool {
+        self.get_tile(tile_pos)
+            .map(|tile| tile.tile_type.is_walkable() && tile.structure.is_walkable())
+            .unwrap_or(false)
+    }
+}
+
+pub struct AtlasSource {
+    pub key: AtlasKey,
+    pub frames: Vec<Handle<Image>>,
+    pub frame_duration: f32,
+}
+
+#[derive(Resource)]
+pub struct TileTextures {
+    pub sources: Vec<AtlasSource>,
+}
+
+pub struct AtlasAnimation {
+    pub frames: Vec<usize>,
+    pub frame_duration: f32,
+}
+
+impl AtlasAnimation {
+    pub fn frame(&self, elapsed: f32, frame_offset: usize) -> usize {
+        if self.frames.len() == 1 || self.frame_duration <= 0.0 {
+            return self.frames[0];
+        }
+        let frame = (elapsed / self.frame_duration) as usize + frame_offset;
+        self.frames[frame % self.frames.len()]
+    }
+}
+
+#[derive(Resource)]
+pub struct TileAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub material: Handle<ColorMaterial>,
+    pub uvs: Vec<Rect>,
+    pub animations: HashMap<AtlasKey, AtlasAnimation>,
+    pub placeholder: usize,
+}
+
+impl TileAtlas {
+    // Anything without a usable entry is drawn with the placeholder so it stands out instead of vanishing
+    pub fn index(&self, key: AtlasKey, elapsed: f32, frame_offset: usize) -> usize {
+        self.animations
+            .get(&key)
+            .map(|animation| animation.frame(elapsed, frame_offset))
+            .unwrap_or(self.placeholder)
+    }
+
+    pub fn uv_rect(&self, key: AtlasKey, elapsed: f32) -> Rect {
+        self.uvs[self.index(key, elapsed, 0)]
+    }
+
+    pub fn is_animated(&self, key: AtlasKey) -> bool {
+        self.animations
+            .get(&key)
+            .map(|animation| animation.frames.len() > 1)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TileAnimationClock {
+    pub elapsed: f32,
+    pub previous: f32,
+}
+
+#[cfg(debug_assertions)]
+#[derive(Resource)]
+pub struct DefinitionsWatch {
+    // Modification times of the definition files, None until first checked
+    pub modified: Option<Vec<Option<std::time::SystemTime>>>,
+    pub timer: Timer,
+}
+
+#[cfg(debug_assertions)]
+impl Default for DefinitionsWatch {
+    fn default() -> Self {
+        DefinitionsWatch {
+            modified: None,
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct LoadedChunks {
+    pub chunks: HashMap<IVec2, Entity>,
+}
+
+#[derive(Resource)]
+pub struct ChunkStreaming {
+    pub radius: i32,
+    pub max_spawns_per_frame: usize,
+}
+
+impl Default for ChunkStreaming {
+    fn default() -> Self {
+        ChunkStreaming {
+            radius: 3,
+            max_spawns_per_frame: 4,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::inventory::items::*;
+use crate::world::registry::*;
+
+// An id into the structure definitions loaded from `assets/structures.ron`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StructureType(u16);
+
+static STRUCTURE_REGISTRY: RwLock<Registry<StructureDefinition>> = RwLock::new(Registry::new());
+
+impl Named for StructureType {
+    fn named(name: &str) -> StructureType {
+        StructureType(intern(&STRUCTURE_REGISTRY, name))
+    }
+
+    fn name(&self) -> &'static str {
+        STRUCTURE_REGISTRY.read().unwrap().name(self.0)
+    }
+}
+
+impl StructureType {
+    pub fn definition(&self) -> StructureDefinition {
+        let registry = STRUCTURE_REGISTRY.read().unwrap();
+        match registry.definition(self.0) {
+            Some(definition) => definition.clone(),
+            None => StructureDefinition::new(*self),
+        }
+    }
+
+    pub fn is_defined(&self) -> bool {
+        STRUCTURE_REGISTRY.read().unwrap().definition(self.0).is_some()
+    }
+
+    pub fn footprint(&self) -> (u8, u8) {
+        let registry = STRUCTURE_REGISTRY.read().unwrap();
+        registry.definition(self.0).map(|definition| definition.footprint).unwrap_or((1, 1))
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        let registry = STRUCTURE_REGISTRY.read().unwrap();
+        registry.definition(self.0).map(|definition| definition.walkable).unwrap_or(false)
+    }
+
+    pub fn sway(&self) -> f32 {
+        let registry = STRUCTURE_REGISTRY.read().unwrap();
+        registry.definition(self.0).map(|definition| definition.sway).unwrap_or(0.0)
+    }
+}
+
+impl Serialize for StructureType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_named(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StructureType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_named(deserializer)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StructureDefinition {
+    pub name: StructureType,
+    // Animation frames, relative to the assets folder
+    pub textures: Vec<String>,
+    pub frame_duration: f32,
+    // Tiles covered, counted right and up from the anchor tile
+    pub footprint: (u8, u8),
+    // Pixels from the centre of the footprint to the centre of the sprite
+    pub offset: (f32, f32),
+    pub walkable: bool,
+    // Colour of the structure in exported map previews
+    pub color: (u8, u8, u8),
+    pub harvest: Option<Harvest>,
+    // Degrees the sprite leans either way in a full gale
+    pub sway: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Harvest {
+    // Seconds the interact action has to be held
+    pub duration: f32,
+    pub drops: Vec<(ItemType, u32)>,
+}
+
+impl StructureDefinition {
+    fn new(name: StructureType) -> Self {
+        StructureDefinition {
+            name,
+            textures: vec![],
+            frame_duration: 0.0,
+            footprint: (1, 1),
+            offset: (0.0, 0.0),
+            walkable: false,
+            color: (255, 0, 255),
+            harvest: None,
+            sway: 0.0,
+        }
+    }
+}
+
+impl Default for StructureDefinition {
+    fn default() -> Self {
+        StructureDefinition::new(StructureType::named("Palm"))
+    }
+}
+
+pub fn structure_definitions() -> Vec<StructureDefinition> {
+    let registry = STRUCTURE_REGISTRY.read().unwrap();
+    registry
+        .order()
+        .iter()
+        .filter_map(|id| registry.definition(*id).cloned())
+        .collect()
+}
+
+pub fn structure_definitions_path() -> PathBuf {
+    PathBuf::from("assets/structures.ron")
+}
+
+pub fn read_structure_definitions() -> Result<Vec<StructureDefinition>, String> {
+    load_definitions(&structure_definitions_path())
+}
+
+pub fn set_structure_definitions(definitions: Vec<StructureDefinition>) {
+    STRUCTURE_REGISTRY.write().unwrap().set_definitions(
+        definitions
+            .into_iter()
+            .map(|definition| (definition.name.0, definition))
+            .collect(),
+    );
+}
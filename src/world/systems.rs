@@ -1,63 +1,94 @@
-use bevy::prelude::*;
-use rand::prelude::*;
-use std::cmp::*;
-use std::iter::*;
-use std::ops::RangeInclusive;
-
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    render::texture::ImageSampler,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    utils::HashMap,
+};
+
+use crate::components::*;
+use crate::controls::resources::*;
 use crate::resources::*;
+use crate::save::resources::*;
+use crate::save::utils::*;
 use crate::world::components::*;
 use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
 use crate::world::utils::*;
 
 pub const CHUNK_SIZE: i32 = 20;
 pub const TILE_SIZE: f32 = 32.0;
-pub const WORLD_SIZE: i32 = (CHUNK_SIZE * (TILE_SIZE as i32) + 15) / 16 * 16;
 
-pub fn create_world(mut reader: EventReader<GameStart>, mut the_world: ResMut<TheWorld>) {
+// Depth layers, back to front. Everything with a `YSort` lands between the
+// y-sort bounds by the world y of its base, see `y_sort_depth`.
+pub const GROUND_Z: f32 = 0.0;
+pub const Y_SORT_MIN_Z: f32 = 1.0;
+pub const Y_SORT_MAX_Z: f32 = 899.0;
+pub const AIRBORNE_Z: f32 = 900.0;
+// World-space markers such as cursors and highlights
+pub const OVERLAY_Z: f32 = 950.0;
+pub const DEBUG_Z: f32 = 990.0;
+
+pub fn create_world(
+    mut reader: EventReader<GameStart>,
+    mut the_world: ResMut<TheWorld>,
+    world_seed: Res<WorldSeed>,
+    world_generator: Res<WorldGenerator>,
+    world_map: Res<WorldMap>,
+    pending_load: Option<Res<PendingLoad>>,
+) {
     if let Some(_game_start) = reader.read().last() {
-        let (world, chunk_biomes) = generate_world();
-        the_world.new(world, chunk_biomes);
+        if let Some(pending_load) = pending_load {
+            pending_load.save.world.restore(&mut the_world);
+            return;
+        }
+        match game_map_path(&world_map).map(|path| read_map(&path)) {
+            Some(Ok(map)) => map.restore(&mut the_world),
+            Some(Err(err)) => {
+                error!("Generating a world instead, failed to load the map {}", err);
+                the_world.reset(world_seed.seed, world_generator.kind);
+            }
+            None => the_world.reset(world_seed.seed, world_generator.kind),
+        }
     }
 }
 
 pub fn despawn_world(
     mut commands: Commands,
     mut reader: EventReader<GameOver>,
-    world_query: Query<Entity, With<Tile>>,
-    chunk_line_query: Query<Entity, With<ChunkLine>>,
-    palm_query: Query<Entity, With<PalmTree>>,
+    mut the_world: ResMut<TheWorld>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    chunk_query: Query<Entity, With<WorldChunk>>,
 ) {
     if let Some(_game_over) = reader.read().last() {
-        for world_entity in world_query.iter() {
-            commands.entity(world_entity).despawn_recursive();
-        }
-        for palm_entity in palm_query.iter() {
-            commands.entity(palm_entity).despawn_recursive()
+        for chunk_entity in chunk_query.iter() {
+            commands.entity(chunk_entity).despawn_recursive();
         }
-        despawn_chunk_outlines(chunk_line_query, commands);
+        loaded_chunks.chunks.clear();
+        the_world.chunks.clear();
     }
 }
 
 pub fn toggle_chunk_outlines(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<Action>>,
     mut chunk_line_render_state: ResMut<NextState<ChunkLineRenderState>>,
     chunk_line_render_state_const: Res<State<ChunkLineRenderState>>,
     chunk_line_query: Query<Entity, With<ChunkLine>>,
+    chunk_query: Query<Entity, With<WorldChunk>>,
 ) {
     match *chunk_line_render_state_const.get() {
         ChunkLineRenderState::Off => {
-            if keyboard_input.just_pressed(KeyCode::KeyB) {
-                for chunk_x in 0..WORLD_SIZE / CHUNK_SIZE {
-                    for chunk_y in 0..WORLD_SIZE / CHUNK_SIZE {
-                        render_chunk_outline(&mut commands, chunk_x, chunk_y);
-                    }
+            if actions.just_pressed(Action::ToggleChunkLines) {
+                for chunk_entity in chunk_query.iter() {
+                    commands.entity(chunk_entity).with_children(render_chunk_outline);
                 }
                 chunk_line_render_state.set(ChunkLineRenderState::On)
             }
         }
         ChunkLineRenderState::On => {
-            if keyboard_input.just_pressed(KeyCode::KeyB) {
+            if actions.just_pressed(Action::ToggleChunkLines) {
                 despawn_chunk_outlines(chunk_line_query, commands);
                 chunk_line_render_state.set(ChunkLineRenderState::Off)
             }
@@ -65,169 +96,188 @@ pub fn toggle_chunk_outlines(
     }
 }
 
-pub fn render_world(
-    mut commands: Commands,
-    the_world: ResMut<TheWorld>,
-    assets: Res<AssetServer>,
-    mut reader: EventReader<GameStart>,
-) {
-    if let Some(_game_over) = reader.read().last() {
-        let ground_material: Handle<Image> = assets.load("sprites/ground.png").into();
-        let thud_material: Handle<Image> = assets.load("sprites/thud.png").into();
-        let grass_material: Handle<Image> = assets.load("sprites/grass.png").into();
-        let water_material: Handle<Image> = assets.load("sprites/water.png").into();
-
-        for row in the_world.world.clone() {
-            for tile in row {
-                commands.spawn((
-                    SpriteBundle {
-                        texture: match tile.tile_type {
-                            TileType::Ground => ground_material.clone(),
-                            TileType::Thud => thud_material.clone(),
-                            TileType::Grass => grass_material.clone(),
-                            TileType::Water => water_material.clone(),
-                        },
-                        transform: Transform::from_xyz(tile.pos.x, tile.pos.y, 0.0),
-                        ..default()
-                    },
-                    tile,
-                ));
-                spawn_structure(tile, &mut commands, &assets);
-            }
-        }
-    }
+pub fn load_worldgen(mut commands: Commands) {
+    let worldgen_config = load_worldgen_config().unwrap_or_else(|err| {
+        error!("Using the built-in world generation, failed to load {}", err);
+        WorldGenConfig::default()
+    });
+    commands.insert_resource(worldgen_config);
 }
 
-fn spawn_structure(tile: Tile, commands: &mut Commands, assets: &Res<AssetServer>) {
-    let double_palm_handle: Handle<Image> = assets.load("sprites/palmtree2.png").into();
-    let single_palm_handle: Handle<Image> = assets.load("sprites/palmtree.png").into();
-    let mut rng = rand::thread_rng();
-    let structure = tile.structure;
-
-    match structure {
-        Structure::Tree => {
-            let palm_handle: Handle<Image>;
-            if rng.gen::<f32>() < 0.05 {
-                palm_handle = single_palm_handle.clone();
-            } else {
-                palm_handle = double_palm_handle.clone()
-            }
-            commands.spawn((
-                SpriteBundle {
-                    transform: Transform::from_xyz(tile.pos.x, tile.pos.y, 0.2),
-                    texture: palm_handle.clone(),
-                    ..default()
-                },
-                PalmTree {},
-            ));
-        }
-        Structure::None => {}
+pub fn load_world_definitions() {
+    if let Err(err) = load_tile_and_structure_definitions() {
+        error!("Failed to load definitions: {}", err);
     }
 }
 
-fn generate_world() -> (Vec<Vec<Tile>>, Vec<Vec<TileType>>) {
-    const POTENTIAL_BIOMES_MULTI: f32 = 100.9;
-    const RANDOM_BIOME_CHANCE: f32 = 0.0001;
-    const WATER_CHANCE: f32 = 0.005;
+pub fn load_tile_textures(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(tile_textures(&assets));
+}
 
-    let mut rng = rand::thread_rng();
-    let mut world: Vec<Vec<Tile>> = vec![vec![]; WORLD_SIZE as usize];
+fn tile_textures(assets: &AssetServer) -> TileTextures {
+    TileTextures {
+        sources: atlas_sources()
+            .into_iter()
+            .map(|(key, paths, frame_duration)| AtlasSource {
+                key,
+                frames: paths.into_iter().map(|path| assets.load(path)).collect(),
+                frame_duration,
+            })
+            .collect(),
+    }
+}
 
-    let spawnable_biomes: Vec<TileType> = vec![TileType::Ground, TileType::Thud, TileType::Grass];
-    let all_biomes: Vec<TileType> = spawnable_biomes
+// Development builds pick up edits to the tile definitions without a restart
+#[cfg(debug_assertions)]
+pub fn reload_world_definitions(
+    mut commands: Commands,
+    mut watch: ResMut<DefinitionsWatch>,
+    time: Res<Time>,
+    assets: Res<AssetServer>,
+) {
+    if !watch.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified: Vec<_> = [structure_definitions_path(), tile_definitions_path()]
         .iter()
-        .cloned()
-        .chain(once(TileType::Water))
+        .map(|path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok())
         .collect();
-
-    let mut chunk_biomes: Vec<Vec<TileType>> = vec![vec![]; WORLD_SIZE as usize / CHUNK_SIZE as usize];
-    for chunk_row in chunk_biomes.iter_mut() {
-        for _ in 0..WORLD_SIZE / CHUNK_SIZE {
-            chunk_row.push(all_biomes[rng.gen_range(0..all_biomes.len())]);
-        }
+    if watch.modified.as_ref() == Some(&modified) {
+        return;
+    }
+    let first_check = watch.modified.is_none();
+    watch.modified = Some(modified);
+    if first_check {
+        return;
+    }
+    if let Err(err) = load_tile_and_structure_definitions() {
+        error!("Failed to reload definitions: {}", err);
+        return;
     }
+    info!("Reloaded the tile and structure definitions");
+    commands.insert_resource(tile_textures(&assets));
+}
 
-    for chunk_x in 0..chunk_biomes.len() {
-        for chunk_y in 0..chunk_biomes[chunk_x].len() {
-            let mut potential_biomes: Vec<TileType> = vec![];
+pub fn build_tile_atlas(
+    mut commands: Commands,
+    tile_textures: Res<TileTextures>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    assets: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let frame_handles = || tile_textures.sources.iter().flat_map(|source| source.frames.iter());
+    if frame_handles().any(|handle| {
+        !matches!(
+            assets.get_load_state(handle),
+            Some(LoadState::Loaded) | Some(LoadState::Failed)
+        )
+    }) {
+        return;
+    }
 
-            let mut check_and_add_neighbor = |x_offset: isize, y_offset: isize| {
-                let neighbor_x = (chunk_x as isize + x_offset) as usize;
-                let neighbor_y = (chunk_y as isize + y_offset) as usize;
+    let placeholder_handle = images.add(placeholder_image());
+    let mut builder = TextureAtlasBuilder::default();
+    builder.add_texture(Some(placeholder_handle.id()), images.get(&placeholder_handle).unwrap());
+    for handle in frame_handles() {
+        // Frames that failed to load are left out and fall back to the placeholder
+        if let Some(image) = images.get(handle) {
+            builder.add_texture(Some(handle.id()), image);
+        }
+    }
 
-                if neighbor_x < chunk_biomes.len()
-                    && neighbor_y < chunk_biomes[neighbor_x].len()
-                    && spawnable_biomes.contains(&chunk_biomes[neighbor_x][neighbor_y])
-                {
-                    for _ in 0..=(rng.gen::<f32>() * POTENTIAL_BIOMES_MULTI) as usize {
-                        potential_biomes.push(chunk_biomes[neighbor_x][neighbor_y].clone());
+    match builder.finish() {
+        Ok((layout, mut atlas_image)) => {
+            // Tiles sit edge to edge in one texture, so filtering would bleed neighbours into each other
+            atlas_image.sampler = ImageSampler::nearest();
+            let uvs = layout
+                .textures
+                .iter()
+                .map(|rect| Rect::from_corners(rect.min / layout.size, rect.max / layout.size))
+                .collect();
+            let animations = tile_textures
+                .sources
+                .iter()
+                .filter_map(|source| {
+                    let frames: Vec<usize> = source
+                        .frames
+                        .iter()
+                        .filter_map(|handle| layout.get_texture_index(handle))
+                        .collect();
+                    if frames.is_empty() {
+                        warn!("No frames loaded for {:?}, using the placeholder", source.key);
+                        return None;
                     }
-                }
-            };
-
-            check_and_add_neighbor(-1, 0);
-            check_and_add_neighbor(0, -1);
-            check_and_add_neighbor(1, 0);
-            check_and_add_neighbor(0, 1);
-
-            potential_biomes.shuffle(&mut rng);
-
-            let tile_type = if rng.gen::<f32>() < WATER_CHANCE {
-                TileType::Water
-            } else {
-                if rng.gen::<f32>() < RANDOM_BIOME_CHANCE {
-                    *spawnable_biomes.choose(&mut rng).unwrap()
-                } else {
-                    if potential_biomes.len() > 0 {
-                        *potential_biomes.choose(&mut rng).unwrap()
-                    } else {
-                        *spawnable_biomes.choose(&mut rng).unwrap()
-                    }
-                }
-            };
-
-            if chunk_x < chunk_biomes.len() && chunk_y < chunk_biomes[chunk_x].len() {
-                chunk_biomes[chunk_x][chunk_y] = tile_type.clone();
-            }
-
-            for x in 0..CHUNK_SIZE {
-                for y in 0..CHUNK_SIZE {
-                    let tile = Tile {
-                        tile_type: tile_type.clone(),
-                        pos: Position {
-                            x: ((chunk_x as i32 * CHUNK_SIZE + x) as f32 - WORLD_SIZE as f32 / 2.0
-                                + 0.5)
-                                * TILE_SIZE,
-                            y: ((chunk_y as i32 * CHUNK_SIZE + y) as f32 - WORLD_SIZE as f32 / 2.0
-                                + 0.5)
-                                * TILE_SIZE,
+                    Some((
+                        source.key,
+                        AtlasAnimation {
+                            frames,
+                            frame_duration: source.frame_duration,
                         },
-                        structure: Structure::None,
-                    };
-                    world[(chunk_y as i32 * CHUNK_SIZE + y) as usize].push(tile);
-                }
+                    ))
+                })
+                .collect();
+            let placeholder = layout.get_texture_index(&placeholder_handle).unwrap();
+            let image = images.add(atlas_image);
+
+            commands.insert_resource(TileAtlas {
+                image: image.clone(),
+                layout: layouts.add(layout),
+                material: materials.add(ColorMaterial::from(image)),
+                uvs,
+                animations,
+                placeholder,
+            });
+            commands.remove_resource::<TileTextures>();
+
+            // Chunks built against an older atlas are respawned by streaming
+            for (_, chunk_entity) in loaded_chunks.chunks.drain() {
+                commands.entity(chunk_entity).despawn_recursive();
             }
         }
+        Err(err) => error!("Failed to build the tile atlas: {:?}", err),
     }
-    blend_biomes(&mut world, &chunk_biomes);
-    fill_world(&mut world, &chunk_biomes);
-    return (world, chunk_biomes);
 }
 
-fn fill_world(world: &mut Vec<Vec<Tile>>, chunk_biomes: &[Vec<TileType>]) {
-    pub const SPAWN_TREE_CHANCE: f32 = 0.01;
-    let mut rng = rand::thread_rng();
+pub fn advance_animation_clock(mut clock: ResMut<TileAnimationClock>, time: Res<Time>) {
+    clock.previous = clock.elapsed;
+    clock.elapsed += time.delta_seconds();
+}
 
-    for chunk_x in 0..WORLD_SIZE / CHUNK_SIZE {
-        for chunk_y in 0..WORLD_SIZE / CHUNK_SIZE {
-            if chunk_biomes[chunk_x as usize][chunk_y as usize] == TileType::Grass {
-                for x in 0..CHUNK_SIZE {
-                    for y in 0..CHUNK_SIZE {
-                        if rng.gen::<f32>() < SPAWN_TREE_CHANCE {
-                            let world_x: f32 = (chunk_x as i32 * CHUNK_SIZE + x) as f32;
-                            let world_y: f32 = (chunk_y as i32 * CHUNK_SIZE + y) as f32;
+pub fn animate_chunk_tiles(
+    the_world: Res<TheWorld>,
+    loaded_chunks: Res<LoadedChunks>,
+    tile_atlas: Res<TileAtlas>,
+    clock: Res<TileAnimationClock>,
+    mesh_query: Query<&Mesh2dHandle, With<WorldChunk>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let advanced_keys: Vec<AtlasKey> = tile_atlas
+        .animations
+        .keys()
+        .filter(|key| {
+            tile_atlas.is_animated(**key)
+                && tile_atlas.index(**key, clock.previous, 0) != tile_atlas.index(**key, clock.elapsed, 0)
+        })
+        .copied()
+        .collect();
+    if advanced_keys.is_empty() {
+        return;
+    }
 
-                            world[world_y as usize][world_x as usize].structure = Structure::Tree;
+    for (chunk_pos, chunk_entity) in loaded_chunks.chunks.iter() {
+        if let (Some(chunk), Ok(mesh_handle)) = (the_world.chunks.get(chunk_pos), mesh_query.get(*chunk_entity)) {
+            if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+                for (y, row) in chunk.tiles.iter().enumerate() {
+                    for (x, tile) in row.iter().enumerate() {
+                        let key = AtlasKey::Tile(tile.tile_type);
+                        if advanced_keys.contains(&key) {
+                            write_tile_uvs(
+                                mesh,
+                                IVec2::new(x as i32, y as i32),
+                                tile_atlas.uv_rect(key, clock.elapsed),
+                            );
                         }
                     }
                 }
@@ -236,85 +286,222 @@ fn fill_world(world: &mut Vec<Vec<Tile>>, chunk_biomes: &[Vec<TileType>]) {
     }
 }
 
-fn blend_biomes(world: &mut Vec<Vec<Tile>>, chunk_biomes: &[Vec<TileType>]) {
-    const BLEND_RANGE: RangeInclusive<i32> = -5..=5;
-    const BLEND_CHANCE: f32 = 0.41;
-
-    let mut rng = rand::thread_rng();
-    for chunk_x in 0..WORLD_SIZE / CHUNK_SIZE {
-        for chunk_y in 0..WORLD_SIZE / CHUNK_SIZE {
-            for x in 0..CHUNK_SIZE {
-                for y in 0..CHUNK_SIZE {
-                    let world_x: usize = (chunk_x * CHUNK_SIZE + x) as usize;
-                    let world_y: usize = (chunk_y * CHUNK_SIZE + y) as usize;
+pub fn animate_sprites(
+    tile_atlas: Res<TileAtlas>,
+    clock: Res<TileAnimationClock>,
+    mut sprite_query: Query<(&AnimatedSprite, &mut TextureAtlas)>,
+) {
+    for (animated_sprite, mut atlas) in sprite_query.iter_mut() {
+        let index = tile_atlas.index(animated_sprite.key, clock.elapsed, animated_sprite.frame_offset);
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+}
 
-                    let current_tile_type: TileType = world[world_y][world_x].tile_type.clone();
+pub fn stream_chunks(
+    mut commands: Commands,
+    mut the_world: ResMut<TheWorld>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    chunk_streaming: Res<ChunkStreaming>,
+    worldgen_config: Res<WorldGenConfig>,
+    chunk_line_render_state: Res<State<ChunkLineRenderState>>,
+    camera_query: Query<&Transform, With<PlayerCamera>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    tile_atlas: Res<TileAtlas>,
+    clock: Res<TileAnimationClock>,
+    mut tiles_changed_event_writer: EventWriter<TilesChanged>,
+) {
+    if let Ok(camera_transform) = camera_query.get_single() {
+        let center = tile_to_chunk(world_to_tile(camera_transform.translation.truncate()));
+        let radius = chunk_streaming.radius;
+
+        // Chunks get one chunk of slack before unloading so walking along a
+        // border does not keep respawning the same row
+        loaded_chunks.chunks.retain(|chunk_pos, chunk_entity| {
+            let keep = (*chunk_pos - center).abs().max_element() <= radius + 1;
+            if !keep {
+                commands.entity(*chunk_entity).despawn_recursive();
+            }
+            keep
+        });
+
+        let mut missing_chunks: Vec<IVec2> = vec![];
+        for chunk_y in -radius..=radius {
+            for chunk_x in -radius..=radius {
+                let chunk_pos = center + IVec2::new(chunk_x, chunk_y);
+                if !loaded_chunks.chunks.contains_key(&chunk_pos) {
+                    missing_chunks.push(chunk_pos);
+                }
+            }
+        }
+        missing_chunks.sort_by_key(|chunk_pos| (*chunk_pos - center).length_squared());
+
+        let mut generated_tiles: Vec<IVec2> = vec![];
+        for chunk_pos in missing_chunks.into_iter().take(chunk_streaming.max_spawns_per_frame) {
+            if !the_world.chunks.contains_key(&chunk_pos) {
+                let chunk = the_world.generate_chunk(&worldgen_config, chunk_pos);
+                the_world.chunks.insert(chunk_pos, chunk);
+                generated_tiles.extend(chunk_border_tiles(chunk_pos));
+            }
+            let chunk = &the_world.chunks[&chunk_pos];
+            let chunk_entity =
+                render_chunk(&mut commands, &mut meshes, &tile_atlas, &clock, chunk_pos, chunk);
+            if *chunk_line_render_state.get() == ChunkLineRenderState::On {
+                commands.entity(chunk_entity).with_children(render_chunk_outline);
+            }
+            loaded_chunks.chunks.insert(chunk_pos, chunk_entity);
+        }
+        if !generated_tiles.is_empty() {
+            tiles_changed_event_writer.send(TilesChanged {
+                tiles: generated_tiles,
+            });
+        }
+    }
+}
 
-                    if current_tile_type == TileType::Water {
-                        continue;
-                    }
-                    let mut possible_tile_types =
-                        vec![TileType::Ground, TileType::Thud, TileType::Grass, TileType::Water];
-                    possible_tile_types.shuffle(&mut rng);
+// Patches the mesh and swaps the structure sprite of every changed tile. Chunks
+// that are not loaded, or only spawned this frame, already draw the new tiles.
+pub fn refresh_dirty_tiles(
+    mut commands: Commands,
+    mut the_world: ResMut<TheWorld>,
+    loaded_chunks: Res<LoadedChunks>,
+    tile_atlas: Res<TileAtlas>,
+    clock: Res<TileAnimationClock>,
+    mesh_query: Query<&Mesh2dHandle, With<WorldChunk>>,
+    structure_query: Query<(Entity, &StructureSprite)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut tiles_changed_event_writer: EventWriter<TilesChanged>,
+) {
+    if the_world.dirty_tiles.is_empty() {
+        return;
+    }
+    let mut dirty_tiles = std::mem::take(&mut the_world.dirty_tiles);
+    dirty_tiles.sort_by_key(|tile_pos| (tile_pos.x, tile_pos.y));
+    dirty_tiles.dedup();
+    tiles_changed_event_writer.send(TilesChanged {
+        tiles: dirty_tiles.clone(),
+    });
+
+    let structure_sprites: HashMap<IVec2, Entity> = structure_query
+        .iter()
+        .map(|(structure_entity, structure_sprite)| (structure_sprite.anchor, structure_entity))
+        .collect();
 
-                    for &possible_tile_type in &possible_tile_types {
-                        if possible_tile_type == current_tile_type {
-                            continue;
-                        }
+    for tile_pos in dirty_tiles {
+        let Some(&chunk_entity) = loaded_chunks.chunks.get(&tile_to_chunk(tile_pos)) else {
+            continue;
+        };
+        let (Ok(mesh_handle), Some(tile)) = (mesh_query.get(chunk_entity), the_world.get_tile(tile_pos)) else {
+            continue;
+        };
+        if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+            let uv_rect = tile_atlas.uv_rect(AtlasKey::Tile(tile.tile_type), clock.elapsed);
+            write_tile_uvs(mesh, tile_to_local(tile_pos), uv_rect);
+        }
 
-                        let mut is_near_desired_chunk = false;
-
-                        'outer: for dx in -1..=1 {
-                            for dy in -1..=1 {
-                                if dx == 0 && dy == 0 {
-                                    continue;
-                                }
-
-                                let check_chunk_x = (chunk_x as i32 + dx) as usize;
-                                let check_chunk_y = (chunk_y as i32 + dy) as usize;
-
-                                if check_chunk_x < chunk_biomes.len()
-                                    && check_chunk_y < chunk_biomes[chunk_x as usize].len()
-                                    && chunk_biomes[check_chunk_x][check_chunk_y] == possible_tile_type
-                                {
-                                    is_near_desired_chunk = true;
-                                    break 'outer;
-                                }
-                            }
-                        }
+        if let Some(&structure_entity) = structure_sprites.get(&tile_pos) {
+            commands.entity(structure_entity).despawn_recursive();
+        }
+        let origin = chunk_origin(tile_to_chunk(tile_pos)).as_vec2() * TILE_SIZE;
+        let tile = *tile;
+        commands
+            .entity(chunk_entity)
+            .with_children(|parent| spawn_structure(tile, origin, parent, &tile_atlas, &clock));
+    }
+}
 
-                        if is_near_desired_chunk {
-                            let mut tile_distance = i32::MAX;
-
-                            for dx in BLEND_RANGE.clone() {
-                                let check_x = world_x.wrapping_add(dx as usize);
-                                if check_x < world[world_y].len()
-                                    && world[world_y][check_x].tile_type == possible_tile_type
-                                {
-                                    tile_distance = tile_distance.min(dx.abs());
-                                }
-                            }
-
-                            for dy in BLEND_RANGE.clone() {
-                                let check_y = world_y.wrapping_add(dy as usize);
-                                if check_y < world.len()
-                                    && world[check_y][world_x].tile_type == possible_tile_type
-                                {
-                                    tile_distance = tile_distance.min(dy.abs());
-                                }
-                            }
-
-                            if BLEND_RANGE.contains(&tile_distance) {
-                                let chance: f32 = BLEND_CHANCE - tile_distance as f32 / 8.0;
-                                if rng.gen::<f32>() < chance {
-                                    world[world_y][world_x].tile_type = possible_tile_type;
-                                    break;
-                                }
-                            }
-                        }
-                    }
+fn render_chunk(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    tile_atlas: &TileAtlas,
+    clock: &TileAnimationClock,
+    chunk_pos: IVec2,
+    chunk: &Chunk,
+) -> Entity {
+    let origin = chunk_origin(chunk_pos).as_vec2() * TILE_SIZE;
+
+    commands
+        .spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(build_chunk_mesh(chunk, tile_atlas, clock.elapsed)).into(),
+                material: tile_atlas.material.clone(),
+                transform: Transform::from_xyz(origin.x, origin.y, GROUND_Z),
+                ..default()
+            },
+            WorldChunk {},
+        ))
+        .with_children(|parent| {
+            for row in chunk.tiles.iter() {
+                for tile in row.iter() {
+                    spawn_structure(*tile, origin, parent, tile_atlas, clock);
                 }
             }
+        })
+        .id()
+}
+
+fn spawn_structure(
+    tile: Tile,
+    origin: Vec2,
+    parent: &mut ChildBuilder,
+    tile_atlas: &TileAtlas,
+    clock: &TileAnimationClock,
+) {
+    // Larger structures are drawn once, from their anchor tile
+    let Structure::Anchor(structure_type) = tile.structure else {
+        return;
+    };
+    let definition = structure_type.definition();
+    let footprint = Vec2::new(definition.footprint.0 as f32, definition.footprint.1 as f32).max(Vec2::ONE);
+    let center = Vec2::new(tile.pos.x, tile.pos.y) + (footprint - Vec2::ONE) * TILE_SIZE / 2.0
+        + Vec2::new(definition.offset.0, definition.offset.1);
+    // Structures sort by the bottom edge of their footprint
+    let base_y = tile.pos.y - TILE_SIZE / 2.0;
+
+    let key = AtlasKey::Structure(structure_type);
+    // Offsetting by position keeps neighbouring structures from animating in lockstep
+    let frame_offset = (tile.pos.x / TILE_SIZE + tile.pos.y / TILE_SIZE).abs() as usize;
+    parent.spawn((
+        SpriteSheetBundle {
+            transform: Transform::from_xyz(center.x - origin.x, center.y - origin.y, y_sort_depth(base_y)),
+            texture: tile_atlas.image.clone(),
+            atlas: TextureAtlas {
+                layout: tile_atlas.layout.clone(),
+                index: tile_atlas.index(key, clock.elapsed, frame_offset),
+            },
+            ..default()
+        },
+        StructureSprite {
+            anchor: world_to_tile(Vec2::new(tile.pos.x, tile.pos.y)),
+        },
+        AnimatedSprite { key, frame_offset },
+        YSort {
+            base_offset: base_y - center.y,
+            airborne: false,
+        },
+    ));
+}
+
+// Runs after everything that moves sortables, children are placed relative to
+// their parent so the world y of their base is worked out through it
+pub fn y_sort(
+    mut sorted_query: Query<(&mut Transform, &YSort, Option<&Parent>), Or<(Changed<Transform>, Changed<YSort>)>>,
+    parent_query: Query<&Transform, Without<YSort>>,
+) {
+    for (mut transform, y_sort, parent) in sorted_query.iter_mut() {
+        let parent_translation = parent
+            .and_then(|parent| parent_query.get(parent.get()).ok())
+            .map(|parent_transform| parent_transform.translation)
+            .unwrap_or(Vec3::ZERO);
+        let z = if y_sort.airborne {
+            AIRBORNE_Z
+        } else {
+            y_sort_depth(parent_translation.y + transform.translation.y + y_sort.base_offset)
+        } - parent_translation.z;
+        // Only written when it moves, so the change filter skips everything standing still
+        if transform.translation.z != z {
+            transform.translation.z = z;
         }
     }
 }
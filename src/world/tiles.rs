@@ -0,0 +1,201 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::world::registry::*;
+use crate::world::structures::*;
+
+// An id into the tile definitions loaded from `assets/tiles.ron`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileType(u16);
+
+static TILE_REGISTRY: RwLock<Registry<TileDefinition>> = RwLock::new(Registry::new());
+
+impl Named for TileType {
+    fn named(name: &str) -> TileType {
+        TileType(intern(&TILE_REGISTRY, name))
+    }
+
+    fn name(&self) -> &'static str {
+        TILE_REGISTRY.read().unwrap().name(self.0)
+    }
+}
+
+impl TileType {
+    pub fn named(name: &str) -> TileType {
+        Named::named(name)
+    }
+
+    pub fn definition(&self) -> TileDefinition {
+        self.with_definition(|definition| definition.clone())
+    }
+
+    pub fn with_definition<R>(&self, f: impl FnOnce(&TileDefinition) -> R) -> R {
+        let registry = TILE_REGISTRY.read().unwrap();
+        match registry.definition(self.0) {
+            Some(definition) => f(definition),
+            None => f(&TileDefinition::new(*self)),
+        }
+    }
+
+    pub fn is_defined(&self) -> bool {
+        TILE_REGISTRY.read().unwrap().definition(self.0).is_some()
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        self.with_definition(|definition| definition.walkable)
+    }
+
+    pub fn blends(&self) -> bool {
+        self.with_definition(|definition| definition.blends)
+    }
+
+    pub fn wet_speed(&self) -> f32 {
+        self.with_definition(|definition| definition.wet_speed)
+    }
+
+    pub fn move_cost(&self) -> f32 {
+        self.with_definition(|definition| definition.move_cost)
+    }
+}
+
+impl Serialize for TileType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_named(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TileType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_named(deserializer)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TileDefinition {
+    pub name: TileType,
+    // Animation frames, relative to the assets folder
+    pub textures: Vec<String>,
+    pub frame_duration: f32,
+    // Colour of the tile in exported map previews
+    pub color: (u8, u8, u8),
+    pub walkable: bool,
+    // Relative chance of being picked as a chunk biome, zero never spawns on its own
+    pub spawn_weight: f32,
+    // Whether neighbouring biomes may bleed into this one
+    pub blends: bool,
+    // Chance of a structure on any tile, which one is picked by the relative weights
+    pub structure_density: f32,
+    pub structures: Vec<(StructureType, f32)>,
+    // Walking speed multiplier once the rain has soaked the tile
+    pub wet_speed: f32,
+    // How much pathfinding avoids the tile, 1.0 is the cheapest
+    pub move_cost: f32,
+}
+
+impl TileDefinition {
+    fn new(name: TileType) -> Self {
+        TileDefinition {
+            name,
+            textures: vec![],
+            frame_duration: 0.0,
+            color: (255, 0, 255),
+            walkable: true,
+            spawn_weight: 0.0,
+            blends: true,
+            structure_density: 0.0,
+            structures: vec![],
+            wet_speed: 1.0,
+            move_cost: 1.0,
+        }
+    }
+
+    pub fn pick_structure(&self, roll: f32) -> Option<StructureType> {
+        let total: f32 = self.structures.iter().map(|(_, weight)| weight).sum();
+        let mut roll = roll * total;
+        for (structure_type, weight) in &self.structures {
+            if roll < *weight {
+                return Some(*structure_type);
+            }
+            roll -= weight;
+        }
+        self.structures.last().map(|(structure_type, _)| *structure_type)
+    }
+}
+
+impl Default for TileDefinition {
+    fn default() -> Self {
+        TileDefinition::new(TileType::named("Grass"))
+    }
+}
+
+// Defined tiles in the order of the definitions file
+pub fn tile_types() -> Vec<TileType> {
+    TILE_REGISTRY.read().unwrap().order().iter().map(|id| TileType(*id)).collect()
+}
+
+pub fn tile_definitions() -> Vec<TileDefinition> {
+    tile_types().iter().map(|tile_type| tile_type.definition()).collect()
+}
+
+pub fn tile_definitions_path() -> PathBuf {
+    PathBuf::from("assets/tiles.ron")
+}
+
+pub fn read_tile_definitions() -> Result<Vec<TileDefinition>, String> {
+    load_definitions(&tile_definitions_path())
+}
+
+pub fn set_tile_definitions(definitions: Vec<TileDefinition>) {
+    TILE_REGISTRY.write().unwrap().set_definitions(
+        definitions
+            .into_iter()
+            .map(|definition| (definition.name.0, definition))
+            .collect(),
+    );
+}
+
+// Unlike the worldgen config there are no built-in tiles, so a missing file is an error too.
+// Both files are read and checked before either registry changes, so on failure the
+// current tiles and structures are kept together.
+pub fn load_tile_and_structure_definitions() -> Result<(), String> {
+    let structures = read_structure_definitions()?;
+    let tiles = read_tile_definitions()?;
+    check_tile_structures(&tiles, &structures)?;
+    set_structure_definitions(structures);
+    set_tile_definitions(tiles);
+    Ok(())
+}
+
+fn check_tile_structures(tiles: &[TileDefinition], structures: &[StructureDefinition]) -> Result<(), String> {
+    for tile in tiles {
+        for (structure_type, _) in &tile.structures {
+            if !structures.iter().any(|structure| structure.name == *structure_type) {
+                return Err(format!(
+                    "{}: {} places {}, which {} does not define",
+                    tile_definitions_path().display(),
+                    tile.name.name(),
+                    structure_type.name(),
+                    structure_definitions_path().display()
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_may_only_place_defined_structures() {
+        let structure = |source| ron::from_str::<StructureDefinition>(source).unwrap();
+        let mut grass = TileDefinition::new(TileType::named("TestGrass"));
+        grass.structures = vec![(StructureType::named("TestPalm"), 1.0)];
+
+        assert!(check_tile_structures(&[grass.clone()], &[structure("(name: TestPalm)")]).is_ok());
+        assert!(check_tile_structures(&[grass], &[structure("(name: TestCoconutPalm)")]).is_err());
+    }
+}
@@ -1,76 +1,223 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
 use crate::world::systems::*;
 use crate::world::components::*;
+use crate::world::resources::*;
+use crate::world::structures::*;
+use crate::world::tiles::*;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
 
-pub fn render_chunk_outline(commands: &mut Commands, chunk_x: i32, chunk_y: i32) {
-    // Define the positions of the chunk's corners
-    let top_left = Vec2::new(
-        (chunk_x as f32 * TILE_SIZE * CHUNK_SIZE as f32) - (WORLD_SIZE as f32 * TILE_SIZE / 2.0),
-        (chunk_y as f32 * TILE_SIZE * CHUNK_SIZE as f32) + (CHUNK_SIZE as f32 * TILE_SIZE)
-            - (WORLD_SIZE as f32 * TILE_SIZE / 2.0),
-    );
-    let bottom_right = Vec2::new(
-        top_left.x + TILE_SIZE * CHUNK_SIZE as f32,
-        top_left.y - TILE_SIZE * CHUNK_SIZE as f32,
-    );
+pub fn worldgen_config_path() -> PathBuf {
+    PathBuf::from("assets/worldgen.ron")
+}
+
+// A missing file falls back to the built-in pipelines, a broken one is an error
+pub fn load_worldgen_config() -> Result<WorldGenConfig, String> {
+    match fs::read_to_string(worldgen_config_path()) {
+        Ok(contents) => ron::from_str(&contents)
+            .map_err(|err| format!("{}: {}", worldgen_config_path().display(), err)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(WorldGenConfig::default()),
+        Err(err) => Err(format!("{}: {}", worldgen_config_path().display(), err)),
+    }
+}
+
+pub fn tile_position(tile_pos: IVec2) -> Position {
+    Position {
+        x: (tile_pos.x as f32 + 0.5) * TILE_SIZE,
+        y: (tile_pos.y as f32 + 0.5) * TILE_SIZE,
+    }
+}
+
+pub fn world_to_tile(world_pos: Vec2) -> IVec2 {
+    (world_pos / TILE_SIZE).floor().as_ivec2()
+}
+
+pub fn tile_to_chunk(tile_pos: IVec2) -> IVec2 {
+    IVec2::new(tile_pos.x.div_euclid(CHUNK_SIZE), tile_pos.y.div_euclid(CHUNK_SIZE))
+}
+
+pub fn tile_to_local(tile_pos: IVec2) -> IVec2 {
+    IVec2::new(tile_pos.x.rem_euclid(CHUNK_SIZE), tile_pos.y.rem_euclid(CHUNK_SIZE))
+}
+
+pub fn chunk_origin(chunk_pos: IVec2) -> IVec2 {
+    chunk_pos * CHUNK_SIZE
+}
+
+// The outermost ring of tiles in a chunk, the ones next to its neighbours
+pub fn chunk_border_tiles(chunk_pos: IVec2) -> Vec<IVec2> {
+    let origin = chunk_origin(chunk_pos);
+    let last = CHUNK_SIZE - 1;
+    let mut tiles = vec![];
+    for offset in 0..CHUNK_SIZE {
+        tiles.push(origin + IVec2::new(offset, 0));
+        tiles.push(origin + IVec2::new(offset, last));
+        if offset != 0 && offset != last {
+            tiles.push(origin + IVec2::new(0, offset));
+            tiles.push(origin + IVec2::new(last, offset));
+        }
+    }
+    tiles
+}
+
+// Every tile a structure anchored here covers, with its offset from the anchor
+pub fn footprint_tiles(anchor: IVec2, structure_type: StructureType) -> impl Iterator<Item = (IVec2, u8, u8)> {
+    let (width, height) = structure_type.footprint();
+    (0..height.max(1)).flat_map(move |dy| {
+        (0..width.max(1)).map(move |dx| (anchor + IVec2::new(dx as i32, dy as i32), dx, dy))
+    })
+}
+
+// Where the cursor points in the world, through the camera's position and zoom
+pub fn cursor_to_world(window: &Window, camera: &Camera, camera_transform: &GlobalTransform) -> Option<Vec2> {
+    window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+}
+
+// Every texture that makes up the tile atlas, as (key, animation frames, seconds per frame)
+pub fn atlas_sources() -> Vec<(AtlasKey, Vec<String>, f32)> {
+    let tiles = tile_definitions()
+        .into_iter()
+        .map(|definition| (AtlasKey::Tile(definition.name), definition.textures, definition.frame_duration));
+    let structures = structure_definitions().into_iter().map(|definition| {
+        (AtlasKey::Structure(definition.name), definition.textures, definition.frame_duration)
+    });
+    tiles.chain(structures).collect()
+}
+
+// Lower on screen draws in front, so the depth falls as the base of a sprite moves up
+pub fn y_sort_depth(base_y: f32) -> f32 {
+    let middle = (Y_SORT_MIN_Z + Y_SORT_MAX_Z) / 2.0;
+    (middle - base_y * 0.001).clamp(Y_SORT_MIN_Z, Y_SORT_MAX_Z)
+}
+
+// Magenta and black checkers, drawn wherever an atlas entry is missing
+pub fn placeholder_image() -> Image {
+    const SIZE: u32 = 32;
+    let mut data: Vec<u8> = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            if (x / 8 + y / 8) % 2 == 0 {
+                data.extend([255, 0, 255, 255]);
+            } else {
+                data.extend([0, 0, 0, 255]);
+            }
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+// Builds one quad per tile, laid out row by row so a tile's vertices can be found from its local position
+pub fn build_chunk_mesh(chunk: &Chunk, tile_atlas: &TileAtlas, elapsed: f32) -> Mesh {
+    let tile_count = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
+    let mut normals: Vec<[f32; 3]> = Vec::with_capacity(tile_count * 4);
+    let mut indices: Vec<u32> = Vec::with_capacity(tile_count * 6);
+
+    for y in 0..CHUNK_SIZE {
+        for x in 0..CHUNK_SIZE {
+            let (left, bottom) = (x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+            let (right, top) = (left + TILE_SIZE, bottom + TILE_SIZE);
+            let first_vertex = positions.len() as u32;
+
+            positions.extend([[left, bottom, 0.0], [right, bottom, 0.0], [right, top, 0.0], [left, top, 0.0]]);
+            normals.extend([[0.0, 0.0, 1.0]; 4]);
+            indices.extend([0, 1, 2, 0, 2, 3].map(|index| first_vertex + index));
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; tile_count * 4])
+        .with_inserted_indices(Indices::U32(indices));
+
+    for (y, row) in chunk.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let uv_rect = tile_atlas.uv_rect(AtlasKey::Tile(tile.tile_type), elapsed);
+            write_tile_uvs(&mut mesh, IVec2::new(x as i32, y as i32), uv_rect);
+        }
+    }
+    mesh
+}
+
+pub fn write_tile_uvs(mesh: &mut Mesh, local: IVec2, uv_rect: Rect) {
+    if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
+        let first_vertex = ((local.y * CHUNK_SIZE + local.x) * 4) as usize;
+        // Image rows run top to bottom, so the quad's top edge samples the rect's min y
+        uvs[first_vertex] = [uv_rect.min.x, uv_rect.max.y];
+        uvs[first_vertex + 1] = [uv_rect.max.x, uv_rect.max.y];
+        uvs[first_vertex + 2] = [uv_rect.max.x, uv_rect.min.y];
+        uvs[first_vertex + 3] = [uv_rect.min.x, uv_rect.min.y];
+    }
+}
+
+pub fn render_chunk_outline(parent: &mut ChildBuilder) {
+    // Positions are local to the chunk entity, whose origin is the chunk's bottom left corner
+    let chunk_width = CHUNK_SIZE as f32 * TILE_SIZE;
+    let top_left = Vec2::new(0.0, chunk_width);
+    let bottom_right = Vec2::new(chunk_width, 0.0);
 
     // Render the top line
-    commands.spawn((SpriteBundle {
+    parent.spawn((SpriteBundle {
         sprite: Sprite {
             color: Color::rgb(1.0, 1.0, 1.0),
-            custom_size: Some(Vec2::new(CHUNK_SIZE as f32 * TILE_SIZE, 2.0)),
+            custom_size: Some(Vec2::new(chunk_width, 2.0)),
             ..default()
         },
-        transform: Transform::from_xyz(
-            top_left.x + CHUNK_SIZE as f32 * TILE_SIZE / 2.0,
-            top_left.y,
-            1.0,
-        ),
+        transform: Transform::from_xyz(top_left.x + chunk_width / 2.0, top_left.y, DEBUG_Z),
         ..default()
     },ChunkLine {}));
 
     // Render the bottom line
-    commands.spawn((SpriteBundle {
+    parent.spawn((SpriteBundle {
         sprite: Sprite {
             color: Color::rgb(1.0, 1.0, 1.0),
-            custom_size: Some(Vec2::new(CHUNK_SIZE as f32 * TILE_SIZE, 2.0)),
+            custom_size: Some(Vec2::new(chunk_width, 2.0)),
             ..default()
         },
-        transform: Transform::from_xyz(
-            bottom_right.x - CHUNK_SIZE as f32 * TILE_SIZE / 2.0,
-            bottom_right.y,
-            1.0,
-        ),
+        transform: Transform::from_xyz(bottom_right.x - chunk_width / 2.0, bottom_right.y, DEBUG_Z),
         ..default()
     },ChunkLine {}));
 
     // Render the left line
-    commands.spawn((SpriteBundle {
+    parent.spawn((SpriteBundle {
         sprite: Sprite {
             color: Color::rgb(1.0, 1.0, 1.0),
-            custom_size: Some(Vec2::new(2.0, CHUNK_SIZE as f32 * TILE_SIZE)),
+            custom_size: Some(Vec2::new(2.0, chunk_width)),
             ..default()
         },
-        transform: Transform::from_xyz(
-            top_left.x,
-            top_left.y - CHUNK_SIZE as f32 * TILE_SIZE / 2.0,
-            1.0,
-        ),
+        transform: Transform::from_xyz(top_left.x, top_left.y - chunk_width / 2.0, DEBUG_Z),
         ..default()
     },ChunkLine {}));
 
     // Render the right line
-    commands.spawn((SpriteBundle {
+    parent.spawn((SpriteBundle {
         sprite: Sprite {
             color: Color::rgb(1.0, 1.0, 1.0),
-            custom_size: Some(Vec2::new(2.0, CHUNK_SIZE as f32 * TILE_SIZE)),
+            custom_size: Some(Vec2::new(2.0, chunk_width)),
             ..default()
         },
-        transform: Transform::from_xyz(
-            bottom_right.x,
-            bottom_right.y + CHUNK_SIZE as f32 * TILE_SIZE / 2.0,
-            1.0,
-        ),
+        transform: Transform::from_xyz(bottom_right.x, bottom_right.y + chunk_width / 2.0, DEBUG_Z),
         ..default()
     },ChunkLine {}));
 }